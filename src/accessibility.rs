@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const TEXT_SCALES: [f32; 3] = [1.0, 1.25, 1.5];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Default,
+    HighContrast,
+    ColourBlind, // okabe-ito colours, safe for the common kinds of colour blindness
+}
+impl Palette {
+    pub fn next(&self) -> Self {
+        match self {
+            Palette::Default => Palette::HighContrast,
+            Palette::HighContrast => Palette::ColourBlind,
+            Palette::ColourBlind => Palette::Default,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    pub text_scale: f32,
+    pub palette: Palette,
+    pub reduced_motion: bool, // no drifting clouds or shaking screen
+    pub auto_fire: bool,      // holding fire keeps shooting
+}
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            text_scale: 1.0,
+            palette: Palette::Default,
+            reduced_motion: false,
+            auto_fire: false,
+        }
+    }
+}
+impl Accessibility {
+    pub fn next_text_scale(&mut self) {
+        let current = TEXT_SCALES
+            .iter()
+            .position(|&scale| scale == self.text_scale)
+            .unwrap_or(0);
+        self.text_scale = TEXT_SCALES[(current + 1) % TEXT_SCALES.len()];
+    }
+}
+
+// every colour that changes with the palette
+#[derive(Resource, Clone, Copy)]
+pub struct Theme {
+    pub background: Color,
+    pub button: Color,
+    pub button_hover: Color,
+    pub button_pressed: Color,
+    pub panel: Color,
+    pub player_two_tint: Color,
+}
+impl Theme {
+    fn from_palette(palette: Palette) -> Self {
+        match palette {
+            Palette::Default => Self {
+                background: Color::hex("#fcdfcd").unwrap(),
+                button: Color::rgb(0.15, 0.15, 0.15),
+                button_hover: Color::rgb(0.3, 0.3, 0.3),
+                button_pressed: Color::rgb(0.1, 0.1, 0.1),
+                panel: Color::hsl(0.0, 0.1, 0.3),
+                player_two_tint: Color::rgb(0.6, 0.8, 1.0),
+            },
+            Palette::HighContrast => Self {
+                background: Color::WHITE,
+                button: Color::BLACK,
+                button_hover: Color::rgb(0.0, 0.2, 0.8),
+                button_pressed: Color::rgb(0.0, 0.1, 0.4),
+                panel: Color::BLACK,
+                player_two_tint: Color::rgb(1.0, 0.9, 0.0),
+            },
+            Palette::ColourBlind => Self {
+                background: Color::hex("#f0e9dc").unwrap(),
+                button: Color::rgb(0.15, 0.15, 0.15),
+                button_hover: Color::hex("#0072b2").unwrap(),
+                button_pressed: Color::hex("#004f7a").unwrap(),
+                panel: Color::hex("#3b3b3b").unwrap(),
+                player_two_tint: Color::hex("#e69f00").unwrap(),
+            },
+        }
+    }
+}
+
+pub struct AccessibilityPlugin;
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Accessibility>()
+            .insert_resource(Theme::from_palette(Palette::default()))
+            .add_systems(
+                PreUpdate,
+                apply_accessibility.run_if(resource_changed::<Accessibility>()),
+            );
+    }
+}
+
+fn apply_accessibility(
+    accessibility: Res<Accessibility>,
+    mut theme: ResMut<Theme>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    *theme = Theme::from_palette(accessibility.palette);
+    ui_scale.0 = accessibility.text_scale as f64;
+}
@@ -0,0 +1,202 @@
+use bevy::{log::Level, prelude::*};
+
+use crate::game::modes::GameMode;
+
+const USAGE: &str = "usage: tile_cat [options]
+
+  --seed <number>          seed every run's bugs with this number
+  --window <WIDTHxHEIGHT>  start with this window size, like 1280x720
+  --fullscreen             start in borderless fullscreen
+  --start-state <state>    skip the main menu: game or settings
+  --mode <mode>            mode for --start-state game: endless, time-attack, pacifist or hardcore
+  --record <file>          write the inputs of each run to a replay file
+  --replay <file>          play a recorded replay back
+  --headless               run without a window or renderer, needs --ticks
+  --ticks <number>         frames to run headless before exiting
+  --config <file>          read and write settings from this file instead of settings.ron
+  --log-level <level>      error, warn, info, debug or trace
+  --help                   show this message
+
+the web build reads the same options from the url, like ?seed=7&mode=pacifist&fullscreen";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartState {
+    Game,
+    Settings,
+}
+
+// everything that can be chosen when launching the game
+#[derive(Resource, Clone, Default)]
+pub struct LaunchOptions {
+    pub seed: Option<u64>,
+    pub window: Option<(f32, f32)>,
+    pub fullscreen: bool,
+    pub start_state: Option<StartState>,
+    pub mode: Option<GameMode>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub headless: bool,
+    pub ticks: Option<u32>,
+    pub config: Option<String>,
+    pub log_level: Option<Level>,
+}
+
+impl LaunchOptions {
+    // parses the command line or the url, printing what went wrong and exiting on bad options
+    pub fn from_env() -> Self {
+        match parse(launch_args()) {
+            Ok(Some(options)) => options,
+            Ok(None) => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Err(error) => {
+                eprintln!("error: {}\n\n{}", error, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn launch_args() -> Vec<String> {
+    std::env::args().skip(1).collect()
+}
+
+// ?seed=7&fullscreen becomes --seed=7 --fullscreen
+#[cfg(target_arch = "wasm32")]
+fn launch_args() -> Vec<String> {
+    let search = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
+
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| format!("--{}", pair))
+        .collect()
+}
+
+// returns None when the usage was asked for
+fn parse(args: Vec<String>) -> Result<Option<LaunchOptions>, String> {
+    let mut options = LaunchOptions::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // both --seed 7 and --seed=7 work
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match flag.as_str() {
+            "--seed" => options.seed = Some(parse_number(&value("--seed")?, "--seed")?),
+            "--window" => options.window = Some(parse_window(&value("--window")?)?),
+            "--fullscreen" => options.fullscreen = true,
+            "--start-state" => {
+                options.start_state = Some(match value("--start-state")?.as_str() {
+                    "game" => StartState::Game,
+                    "settings" => StartState::Settings,
+                    other => {
+                        return Err(format!(
+                            "--start-state must be game or settings, not `{}`",
+                            other
+                        ))
+                    }
+                })
+            }
+            "--mode" => {
+                options.mode = Some(match value("--mode")?.as_str() {
+                    "endless" => GameMode::Endless,
+                    "time-attack" => GameMode::TimeAttack,
+                    "pacifist" => GameMode::Pacifist,
+                    "hardcore" => GameMode::Hardcore,
+                    other => {
+                        return Err(format!(
+                            "--mode must be endless, time-attack, pacifist or hardcore, not `{}`",
+                            other
+                        ))
+                    }
+                })
+            }
+            "--record" => options.record = Some(value("--record")?),
+            "--replay" => options.replay = Some(value("--replay")?),
+            "--headless" => options.headless = true,
+            "--ticks" => options.ticks = Some(parse_number(&value("--ticks")?, "--ticks")?),
+            "--config" => options.config = Some(value("--config")?),
+            "--log-level" => {
+                options.log_level = Some(match value("--log-level")?.as_str() {
+                    "error" => Level::ERROR,
+                    "warn" => Level::WARN,
+                    "info" => Level::INFO,
+                    "debug" => Level::DEBUG,
+                    "trace" => Level::TRACE,
+                    other => {
+                        return Err(format!(
+                            "--log-level must be error, warn, info, debug or trace, not `{}`",
+                            other
+                        ))
+                    }
+                })
+            }
+            "--help" | "-h" => return Ok(None),
+            other => return Err(format!("unknown option `{}`", other)),
+        }
+    }
+
+    validate(&options)?;
+    Ok(Some(options))
+}
+
+// options that make no sense together
+fn validate(options: &LaunchOptions) -> Result<(), String> {
+    if options.headless != options.ticks.is_some() {
+        return Err("--headless and --ticks go together".to_string());
+    }
+    if options.headless && cfg!(target_arch = "wasm32") {
+        return Err("the web build can not run headless".to_string());
+    }
+    if options.record.is_some() && options.replay.is_some() {
+        return Err("--record and --replay can not be used together".to_string());
+    }
+    if options.replay.is_some() && (options.mode.is_some() || options.seed.is_some()) {
+        return Err("the replay file already sets the mode and the seed".to_string());
+    }
+    if options.replay.is_some() && options.start_state == Some(StartState::Settings) {
+        return Err("a replay always starts in the game".to_string());
+    }
+    if options.mode.is_some() && options.start_state != Some(StartState::Game) {
+        return Err("--mode needs --start-state game".to_string());
+    }
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a whole number, not `{}`", name, value))
+}
+
+fn parse_window(value: &str) -> Result<(f32, f32), String> {
+    let error = || {
+        format!(
+            "--window expects WIDTHxHEIGHT like 1280x720, not `{}`",
+            value
+        )
+    };
+
+    let (width, height) = value.split_once('x').ok_or_else(error)?;
+    let width: u32 = width.parse().map_err(|_| error())?;
+    let height: u32 = height.parse().map_err(|_| error())?;
+    if width == 0 || height == 0 {
+        return Err(error());
+    }
+    Ok((width as f32, height as f32))
+}
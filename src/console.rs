@@ -0,0 +1,391 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::game::controlls::CatInputSet;
+
+const DEFAULT_CONSOLE_KEY: KeyCode = KeyCode::Grave;
+const LOG_LENGTH: usize = 12;
+const HISTORY_LENGTH: usize = 32;
+
+// a line typed into the console, sent to the plugin that registered the command
+#[derive(Event)]
+pub struct ConsoleCommandEvent {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub args: Vec<String>,
+}
+impl ConsoleCommandEvent {
+    pub fn arg<T: std::str::FromStr>(&self, index: usize) -> Option<T> {
+        self.args.get(index)?.parse().ok()
+    }
+}
+
+// a line printed to the console log
+#[derive(Event)]
+pub struct ConsoleReply(pub String);
+
+struct ConsoleCommand {
+    name: &'static str, // may span several words, like "spawn bug"
+    usage: &'static str,
+    completions: &'static [&'static [&'static str]], // tab completions for each argument
+}
+
+#[derive(Resource, Default)]
+struct ConsoleCommands(Vec<ConsoleCommand>);
+impl ConsoleCommands {
+    // the longest registered name the line starts with, word by word
+    fn find(&self, line: &str) -> Option<&ConsoleCommand> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        self.0
+            .iter()
+            .filter(|command| {
+                let name_words: Vec<&str> = command.name.split(' ').collect();
+                words.len() >= name_words.len() && words[..name_words.len()] == name_words[..]
+            })
+            .max_by_key(|command| command.name.len())
+    }
+}
+
+// lets each plugin bring its own console commands
+pub trait AddConsoleCommand {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &'static [&'static [&'static str]],
+    ) -> &mut Self;
+}
+impl AddConsoleCommand for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &'static [&'static [&'static str]],
+    ) -> &mut Self {
+        self.add_event::<ConsoleCommandEvent>()
+            .add_event::<ConsoleReply>()
+            .init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .0
+            .push(ConsoleCommand {
+                name,
+                usage,
+                completions,
+            });
+        self
+    }
+}
+
+// the key that opens the console, the bind command changes it
+#[derive(Resource)]
+pub struct ConsoleKey(pub KeyCode);
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    rebinding: bool, // the next key pressed becomes the console key
+    input: String,
+    log: Vec<String>,
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+impl Console {
+    fn print(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleText;
+
+pub struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .insert_resource(ConsoleKey(DEFAULT_CONSOLE_KEY))
+            .add_console_command("help", "help", &[])
+            .add_console_command("clear", "clear", &[])
+            .add_console_command("bind", "bind - then press the new console key", &[])
+            .add_systems(Startup, spawn_console)
+            .add_systems(
+                PreUpdate,
+                // typing must not reach the cats or the menus
+                (toggle_console, read_console_input)
+                    .chain()
+                    .after(InputSystem)
+                    .before(CatInputSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    run_console_commands.run_if(on_event::<ConsoleCommandEvent>()),
+                    print_console_replies.run_if(on_event::<ConsoleReply>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                update_console_text.run_if(resource_changed::<Console>()),
+            );
+    }
+}
+
+fn spawn_console(mut commands: Commands) {
+    let panel = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            height: Val::Percent(40.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::End,
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+        z_index: ZIndex::Global(4),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((panel, ConsolePanel))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::from_section("", text_style), ConsoleText));
+        });
+}
+
+fn toggle_console(
+    mut console: ResMut<Console>,
+    mut console_key: ResMut<ConsoleKey>,
+    mut panel_query: Query<&mut Visibility, With<ConsolePanel>>,
+    mut keys: ResMut<Input<KeyCode>>,
+) {
+    if console.rebinding {
+        let Some(&key) = keys.get_just_pressed().next() else {
+            return;
+        };
+        console_key.0 = key;
+        console.rebinding = false;
+        console.print(format!("console key is now {:?}", key));
+        keys.reset_all();
+        return;
+    }
+
+    if !keys.just_pressed(console_key.0) {
+        return;
+    }
+
+    console.open = !console.open;
+    if !console.open {
+        keys.reset_all();
+    }
+
+    let Ok(mut visibility) = panel_query.get_single_mut() else {
+        return;
+    };
+    *visibility = if console.open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn read_console_input(
+    mut console: ResMut<Console>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut character_reader: EventReader<ReceivedCharacter>,
+    mut command_writer: EventWriter<ConsoleCommandEvent>,
+    commands: Res<ConsoleCommands>,
+    console_key: Res<ConsoleKey>,
+) {
+    if !console.open {
+        character_reader.clear();
+        return;
+    }
+
+    // the key that opened the console also types its character
+    if console.rebinding || keys.just_pressed(console_key.0) {
+        character_reader.clear();
+    }
+    for character in character_reader.read() {
+        if !character.char.is_control() {
+            console.input.push(character.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        complete(&mut console, &commands);
+    }
+    if keys.just_pressed(KeyCode::Up) || keys.just_pressed(KeyCode::Down) {
+        browse_history(&mut console, keys.just_pressed(KeyCode::Up));
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        submit(&mut console, &commands, &mut command_writer, line);
+    }
+
+    keys.reset_all();
+}
+
+fn submit(
+    console: &mut Console,
+    commands: &ConsoleCommands,
+    command_writer: &mut EventWriter<ConsoleCommandEvent>,
+    line: String,
+) {
+    let line = line.trim().to_string();
+    if line.is_empty() {
+        return;
+    }
+
+    console.print(format!("> {}", line));
+    console.history.push(line.clone());
+    if console.history.len() > HISTORY_LENGTH {
+        console.history.remove(0);
+    }
+    console.history_index = None;
+
+    let Some(command) = commands.find(&line) else {
+        console.print(format!("unknown command, try help: {}", line));
+        return;
+    };
+
+    command_writer.send(ConsoleCommandEvent {
+        name: command.name,
+        usage: command.usage,
+        args: line
+            .split_whitespace()
+            .skip(command.name.split(' ').count())
+            .map(str::to_string)
+            .collect(),
+    });
+}
+
+fn browse_history(console: &mut Console, older: bool) {
+    if console.history.is_empty() {
+        return;
+    }
+
+    let last = console.history.len() - 1;
+    console.history_index = match (console.history_index, older) {
+        (None, true) => Some(last),
+        (None, false) => None,
+        (Some(index), true) => Some(index.saturating_sub(1)),
+        (Some(index), false) if index < last => Some(index + 1),
+        (Some(_), false) => None,
+    };
+    console.input = console
+        .history_index
+        .map(|index| console.history[index].clone())
+        .unwrap_or_default();
+}
+
+// completes the command name, or the argument being typed from the command's completions
+fn complete(console: &mut Console, commands: &ConsoleCommands) {
+    let line = console.input.trim_start().to_string();
+
+    let (prefix, partial, candidates): (String, &str, Vec<&str>) = match commands.find(&line) {
+        Some(command) if line.len() > command.name.len() => {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let name_length = command.name.split(' ').count();
+            let (argument, partial) = if line.ends_with(' ') {
+                (words.len() - name_length, "")
+            } else {
+                (words.len() - name_length - 1, *words.last().unwrap_or(&""))
+            };
+            let Some(options) = command.completions.get(argument) else {
+                return;
+            };
+            (
+                line[..line.len() - partial.len()].to_string(),
+                partial,
+                options.to_vec(),
+            )
+        }
+        _ => (
+            String::new(),
+            line.as_str(),
+            commands.0.iter().map(|command| command.name).collect(),
+        ),
+    };
+
+    let matches: Vec<&str> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(partial))
+        .collect();
+
+    match matches.as_slice() {
+        [] => (),
+        [only] => console.input = format!("{}{} ", prefix, only),
+        _ => {
+            let common = matches
+                .iter()
+                .fold(matches[0].to_string(), |common, other| {
+                    common
+                        .chars()
+                        .zip(other.chars())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a)
+                        .collect()
+                });
+            console.input = format!("{}{}", prefix, common);
+            console.print(matches.join("  "));
+        }
+    }
+}
+
+fn run_console_commands(
+    mut console: ResMut<Console>,
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    commands: Res<ConsoleCommands>,
+) {
+    for command in command_reader.read() {
+        match command.name {
+            "help" => {
+                for registered in &commands.0 {
+                    console.print(registered.usage.to_string());
+                }
+            }
+            "clear" => console.log.clear(),
+            "bind" => {
+                console.rebinding = true;
+                console.print("press the new console key".to_string());
+            }
+            _ => (),
+        }
+    }
+}
+
+fn print_console_replies(
+    mut console: ResMut<Console>,
+    mut reply_reader: EventReader<ConsoleReply>,
+) {
+    for ConsoleReply(line) in reply_reader.read() {
+        console.print(line.clone());
+    }
+}
+
+fn update_console_text(mut text_query: Query<&mut Text, With<ConsoleText>>, console: Res<Console>) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut lines = console.log.clone();
+    lines.push(format!("> {}_", console.input));
+    text.sections[0].value = lines.join("\n");
+}
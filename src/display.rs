@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::Instant,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowMoved, WindowResized},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{cli::LaunchOptions, game::particles::ParticleQuality};
+
+// whole multiples of the arena, so every preset scales it without borders
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+const FRAME_CAPS: [Option<u32>; 4] = [None, Some(30), Some(60), Some(144)];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless, // fullscreen window at the desktop resolution
+    Fullscreen, // exclusive fullscreen
+}
+impl DisplayMode {
+    pub fn next(&self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    pub resolution: (u32, u32), // the windowed size, follows the window when it is dragged
+    pub position: Option<(i32, i32)>, // where the window was last, the os picks when None
+    pub vsync: bool,
+    pub frame_cap: Option<u32>, // frames per second, None runs as fast as it can
+    pub particle_quality: ParticleQuality,
+}
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Windowed,
+            resolution: RESOLUTIONS[0],
+            position: None,
+            vsync: true,
+            frame_cap: None,
+            particle_quality: ParticleQuality::default(),
+        }
+    }
+}
+impl DisplaySettings {
+    // the next preset bigger than the current size, back to the smallest after the biggest
+    pub fn next_resolution(&mut self) {
+        self.resolution = RESOLUTIONS
+            .into_iter()
+            .find(|&(width, height)| width * height > self.resolution.0 * self.resolution.1)
+            .unwrap_or(RESOLUTIONS[0]);
+    }
+
+    pub fn next_frame_cap(&mut self) {
+        let current = FRAME_CAPS
+            .iter()
+            .position(|&cap| cap == self.frame_cap)
+            .unwrap_or(0);
+        self.frame_cap = FRAME_CAPS[(current + 1) % FRAME_CAPS.len()];
+    }
+}
+
+pub struct DisplayPlugin;
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .add_systems(
+                PreUpdate,
+                (
+                    apply_display.run_if(resource_changed::<DisplaySettings>()),
+                    apply_launch_options.run_if(run_once()),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                track_window.run_if(on_event::<WindowResized>().or_else(on_event::<WindowMoved>())),
+            )
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+// --window and --fullscreen win over the saved settings for this launch only,
+// so they go straight to the window and are never saved
+fn apply_launch_options(
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    options: Res<LaunchOptions>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some((width, height)) = options.window {
+        window.mode = WindowMode::Windowed;
+        window.resolution.set(width, height);
+    }
+    if options.fullscreen {
+        window.mode = WindowMode::BorderlessFullscreen;
+    }
+}
+
+fn apply_display(
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    display: Res<DisplaySettings>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    window.mode = match display.mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+        DisplayMode::Fullscreen => WindowMode::Fullscreen,
+    };
+    window.present_mode = if display.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+
+    if display.mode == DisplayMode::Windowed {
+        let (width, height) = display.resolution;
+        window.resolution.set(width as f32, height as f32);
+        if let Some((x, y)) = display.position {
+            window.position = WindowPosition::At(IVec2::new(x, y));
+        }
+    }
+}
+
+// remembers the size and place of the window without reapplying them,
+// they are saved with the other settings
+fn track_window(
+    mut display: ResMut<DisplaySettings>,
+    mut resized_reader: EventReader<WindowResized>,
+    mut moved_reader: EventReader<WindowMoved>,
+    options: Res<LaunchOptions>,
+) {
+    let display = display.bypass_change_detection();
+    // a window sized by the launch options would otherwise be saved as the player's
+    if display.mode != DisplayMode::Windowed || options.window.is_some() || options.fullscreen {
+        resized_reader.clear();
+        moved_reader.clear();
+        return;
+    }
+
+    for resized in resized_reader.read() {
+        display.resolution = (resized.width as u32, resized.height as u32);
+    }
+    for moved in moved_reader.read() {
+        display.position = Some((moved.position.x, moved.position.y));
+    }
+}
+
+// sleeps away what is left of the frame, the browser already paces the web build
+// and headless runs go as fast as they can
+fn limit_frame_rate(
+    mut last_frame: Local<Option<Instant>>,
+    display: Res<DisplaySettings>,
+    options: Res<LaunchOptions>,
+) {
+    if cfg!(target_arch = "wasm32") || options.headless {
+        return;
+    }
+
+    if let (Some(cap), Some(last_frame)) = (display.frame_cap, *last_frame) {
+        let frame_time = Duration::from_secs_f64(1.0 / cap as f64);
+        if let Some(remaining) = frame_time.checked_sub(last_frame.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
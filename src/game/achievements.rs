@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bugs::BugKilledEvent, bullet::DestroyBulletEvent, difficulty::DifficultyConfig,
+    stats::RunStats, DifficultyMultiplier, GameState, Score,
+};
+use crate::{
+    locale::Locale,
+    menu::toast::ToastEvent,
+    persistence::{read_file, write_file},
+};
+
+const ACHIEVEMENTS_PATH: &str = "achievements.ron";
+
+#[derive(Clone, Copy)]
+enum Goal {
+    Score(u32),            // reach this score in one run
+    TopDifficulty(u32),    // be at the run's difficulty limit with at least this score
+    KillStreak(u32),       // kill this many bugs in a row without a bullet missing
+    BlocksPlaced(u32),     // place this many blocks in one run
+    FlawlessScore(u32),    // reach this score without losing a heart
+    RunWithoutBlocks(u32), // end a run with at least this score and no blocks placed
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,        // message id
+    pub description: &'static str, // message id
+    goal: Goal,
+}
+
+pub const ACHIEVEMENTS: [Achievement; 7] = [
+    Achievement {
+        id: "first-catch",
+        name: "achievement-first-catch",
+        description: "achievement-first-catch-desc",
+        goal: Goal::Score(5),
+    },
+    Achievement {
+        id: "centurion",
+        name: "achievement-centurion",
+        description: "achievement-centurion-desc",
+        goal: Goal::Score(100),
+    },
+    Achievement {
+        id: "top-speed",
+        name: "achievement-top-speed",
+        description: "achievement-top-speed-desc",
+        goal: Goal::TopDifficulty(50),
+    },
+    Achievement {
+        id: "sharpshooter",
+        name: "achievement-sharpshooter",
+        description: "achievement-sharpshooter-desc",
+        goal: Goal::KillStreak(10),
+    },
+    Achievement {
+        id: "builder",
+        name: "achievement-builder",
+        description: "achievement-builder-desc",
+        goal: Goal::BlocksPlaced(50),
+    },
+    Achievement {
+        id: "untouchable",
+        name: "achievement-untouchable",
+        description: "achievement-untouchable-desc",
+        goal: Goal::FlawlessScore(50),
+    },
+    Achievement {
+        id: "grounded",
+        name: "achievement-grounded",
+        description: "achievement-grounded-desc",
+        goal: Goal::RunWithoutBlocks(50),
+    },
+];
+
+// what the goals are checked against
+struct Progress<'a> {
+    score: u32,
+    at_difficulty_limit: bool,
+    kill_streak: u32,
+    run_stats: &'a RunStats,
+    run_over: bool,
+}
+
+impl Goal {
+    fn reached(&self, progress: &Progress) -> bool {
+        match *self {
+            Goal::Score(score) => progress.score >= score,
+            Goal::TopDifficulty(score) => progress.at_difficulty_limit && progress.score >= score,
+            Goal::KillStreak(kills) => progress.kill_streak >= kills,
+            Goal::BlocksPlaced(blocks) => progress.run_stats.blocks_placed >= blocks,
+            Goal::FlawlessScore(score) => {
+                progress.score >= score && progress.run_stats.hearts_lost.is_empty()
+            }
+            Goal::RunWithoutBlocks(score) => {
+                progress.run_over
+                    && progress.score >= score
+                    && progress.run_stats.blocks_placed == 0
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct UnlockedAchievements(pub HashSet<String>);
+
+// bugs killed since the last bullet that missed
+#[derive(Resource, Default)]
+struct KillStreak(u32);
+
+pub struct AchievementsPlugin;
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UnlockedAchievements>()
+            .init_resource::<KillStreak>()
+            .add_systems(Startup, load_achievements)
+            .add_systems(OnEnter(GameState::Game), reset_kill_streak)
+            .add_systems(OnEnter(GameState::GameOver), check_achievements::<true>)
+            .add_systems(
+                Update,
+                (
+                    track_kill_streak,
+                    check_achievements::<false>.after(track_kill_streak),
+                )
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                Update,
+                save_achievements.run_if(resource_changed::<UnlockedAchievements>()),
+            );
+    }
+}
+
+fn reset_kill_streak(mut kill_streak: ResMut<KillStreak>) {
+    kill_streak.0 = 0;
+}
+
+fn track_kill_streak(
+    mut kill_reader: EventReader<BugKilledEvent>,
+    mut miss_reader: EventReader<DestroyBulletEvent>,
+    mut kill_streak: ResMut<KillStreak>,
+) {
+    kill_streak.0 += kill_reader.read().count() as u32;
+    if miss_reader.read().count() > 0 {
+        kill_streak.0 = 0;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_achievements<const RUN_OVER: bool>(
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut toast_writer: EventWriter<ToastEvent>,
+    score: Res<Score>,
+    difficulty: Res<DifficultyMultiplier>,
+    kill_streak: Res<KillStreak>,
+    run_stats: Res<RunStats>,
+    config: Res<DifficultyConfig>,
+    locale: Res<Locale>,
+) {
+    let progress = Progress {
+        score: score.0,
+        // the score keeps hardcore, which starts at the limit, from unlocking it on the first frame
+        at_difficulty_limit: difficulty.0 >= config.difficulty_upper_limit,
+        kill_streak: kill_streak.0,
+        run_stats: &run_stats,
+        run_over: RUN_OVER,
+    };
+
+    for achievement in &ACHIEVEMENTS {
+        // checked first so the resource is only marked changed on a new unlock
+        if unlocked.0.contains(achievement.id) || !achievement.goal.reached(&progress) {
+            continue;
+        }
+
+        unlocked.0.insert(achievement.id.to_string());
+        toast_writer.send(ToastEvent(
+            locale.format("achievement-unlocked", &[&locale.get(achievement.name)]),
+        ));
+    }
+}
+
+fn load_achievements(mut unlocked: ResMut<UnlockedAchievements>) {
+    let Some(contents) = read_file(ACHIEVEMENTS_PATH) else {
+        return;
+    };
+
+    match ron::from_str(&contents) {
+        Ok(loaded) => *unlocked = loaded,
+        Err(error) => warn!("ignoring broken {}: {}", ACHIEVEMENTS_PATH, error),
+    }
+}
+
+fn save_achievements(unlocked: Res<UnlockedAchievements>) {
+    match ron::ser::to_string_pretty(&*unlocked, default()) {
+        Ok(contents) => {
+            write_file(ACHIEVEMENTS_PATH, &contents);
+        }
+        Err(error) => warn!("could not save achievements: {}", error),
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    reflect::TypePath,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use super::{GameState, SimulationState};
+
+// a run of frames from a sprite sheet, read from the .anim.ron file next to the sheet
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct AnimationClip {
+    pub frames: (usize, usize), // first and last atlas index, both included
+    pub fps: f32,
+    pub looping: bool,
+    pub events: Vec<(usize, String)>, // frames counted from the clip's first, sent as AnimationEvents
+}
+impl Default for AnimationClip {
+    fn default() -> Self {
+        Self {
+            frames: (0, 0),
+            fps: 10.0,
+            looping: true,
+            events: Vec::new(),
+        }
+    }
+}
+impl AnimationClip {
+    fn len(&self) -> usize {
+        self.frames.1.saturating_sub(self.frames.0) + 1
+    }
+}
+
+// how one sprite sheet is cut up and every clip of it by name
+#[derive(Asset, TypePath, Deserialize)]
+pub struct AnimationClips {
+    pub tile_size: (u32, u32), // the sheet holds as many tiles as fit, counted row by row
+    clips: HashMap<String, AnimationClip>,
+}
+
+#[derive(Default)]
+struct AnimationClipsLoader;
+impl AssetLoader for AnimationClipsLoader {
+    type Asset = AnimationClips;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AnimationClips, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+// plays clips on the TextureAtlasSprite of the same entity
+#[derive(Component)]
+pub struct SpriteAnimator {
+    clips: Handle<AnimationClips>,
+    clip: String,
+    frame: usize, // counted from the start of the clip
+    elapsed: f32, // seconds on the current frame
+    started: bool,
+    finished: bool, // only ever set for clips that do not loop
+}
+impl SpriteAnimator {
+    pub fn new(clips: Handle<AnimationClips>, clip: &str) -> Self {
+        Self {
+            clips,
+            clip: clip.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+            started: false,
+            finished: false,
+        }
+    }
+
+    // switches clip, a clip already playing carries on where it is
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.restart();
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.started = false;
+        self.finished = false;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[derive(Event)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub name: String,
+}
+
+// what picks the clips runs before this, what reacts to the frames after
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnimationSet;
+
+pub struct AnimationPlugin;
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationEvent>()
+            .init_asset::<AnimationClips>()
+            .init_asset_loader::<AnimationClipsLoader>()
+            .add_systems(
+                Update,
+                animate_sprites
+                    .in_set(AnimationSet)
+                    .run_if(in_state(GameState::Game))
+                    .run_if(in_state(SimulationState::Running)),
+            );
+    }
+}
+
+fn animate_sprites(
+    mut animator_query: Query<(Entity, &mut SpriteAnimator, &mut TextureAtlasSprite)>,
+    mut event_writer: EventWriter<AnimationEvent>,
+    clips: Res<Assets<AnimationClips>>,
+    time: Res<Time>,
+) {
+    for (entity, mut animator, mut sprite) in &mut animator_query {
+        // nothing to play until the clips have loaded
+        let Some(clip) = clips
+            .get(&animator.clips)
+            .and_then(|clips| clips.clips.get(&animator.clip))
+        else {
+            continue;
+        };
+
+        let mut shown = Vec::new();
+        if !animator.started {
+            animator.started = true;
+            shown.push(0);
+        } else if !animator.finished {
+            animator.elapsed += time.delta_seconds();
+            let frame_time = 1.0 / clip.fps.max(f32::EPSILON);
+            while animator.elapsed >= frame_time {
+                animator.elapsed -= frame_time;
+                if animator.frame + 1 < clip.len() {
+                    animator.frame += 1;
+                } else if clip.looping {
+                    animator.frame = 0;
+                } else {
+                    animator.finished = true;
+                    break;
+                }
+                shown.push(animator.frame);
+            }
+        }
+
+        sprite.index = clip.frames.0 + animator.frame.min(clip.len() - 1);
+        for frame in shown {
+            for (_, name) in clip.events.iter().filter(|(at, _)| *at == frame) {
+                event_writer.send(AnimationEvent {
+                    entity,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+}
@@ -1,38 +1,121 @@
-use bevy::{
-    prelude::*,
-    window::{PrimaryWindow, WindowResized},
+use bevy::{prelude::*, utils::HashSet};
+use rand::Rng;
+use serde::Serialize;
+
+#[cfg(debug_assertions)]
+use crate::console::{AddConsoleCommand, ConsoleCommandEvent, ConsoleReply};
+use crate::{
+    loading::GameAssets,
+    viewport::{ARENA_HEIGHT, ARENA_WIDTH},
 };
-use rand::{random, Rng};
 
 use super::{
-    bullet::Bullet, DifficultyMultiplier, GameState, Heart, Score, SimulationState, SCALE_FACTOR,
+    animation::SpriteAnimator,
+    bullet::Bullet,
+    cat::Cat,
+    day_cycle::{advance_day, DayCycle},
+    difficulty::DifficultyConfig,
+    modes::GameMode,
+    particles::{spawn_emitter, BUG_BURST},
+    rng::GameRng,
+    tuning::Tuning,
+    weather::{CurrentWeather, WeatherSet},
+    DifficultyMultiplier, GameState, Heart, PlayerScores, Score, SimulationState,
 };
 
 pub const BUG_SIZE: f32 = 16.0;
-const BUG_SPAWN_RATE: f32 = 0.84;
-const BUG_SPEED: f32 = 20.0;
-const BUG_VERTICAL_SPEED: f32 = 15.0;
-const BUG_ANIMATION_INTERVAL: f32 = 0.4;
-const BUG_VERTICAL_MOVEMENT_INTERVAL: std::ops::Range<f32> = (1.0)..2.0;
 const SPAWN_HORIZONTAL_PADDING: f32 = 16.0;
+const WIND_SHARE: f32 = 0.5; // bugs fly against the wind, so it only pushes them half as hard
+const MIN_BUG_SPEED: f32 = 0.25; // share of a bug's speed it keeps against any headwind
+
+// in the same order as the atlases in GameAssets
+pub const BUG_SPECIES: [BugSpecies; 5] = [
+    BugSpecies::OrangeFly,
+    BugSpecies::BlueFly,
+    BugSpecies::Crawler,
+    BugSpecies::Firefly,
+    BugSpecies::Moth,
+];
+
+// which species come out at each time of day
+const DAY_SPECIES: [BugSpecies; 3] = [
+    BugSpecies::OrangeFly,
+    BugSpecies::BlueFly,
+    BugSpecies::Crawler,
+];
+const NIGHT_SPECIES: [BugSpecies; 3] = [BugSpecies::Firefly, BugSpecies::Moth, BugSpecies::Crawler];
+
+// how BUG_SPECIES are typed in the console
+#[cfg(debug_assertions)]
+const BUG_SPECIES_NAMES: [&str; 5] = ["orange-fly", "blue-fly", "crawler", "firefly", "moth"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum BugSpecies {
+    OrangeFly,
+    BlueFly,
+    Crawler,
+    Firefly, // only at night
+    Moth,    // only at night
+}
+impl BugSpecies {
+    fn index(&self) -> usize {
+        BUG_SPECIES
+            .iter()
+            .position(|species| species == self)
+            .unwrap_or(0)
+    }
+
+    fn can_fly(&self) -> bool {
+        *self != BugSpecies::Crawler
+    }
+
+    // the clip in bug.anim.ron
+    fn clip(&self) -> &'static str {
+        if self.can_fly() {
+            "fly"
+        } else {
+            "crawl"
+        }
+    }
+
+    // the night bugs are recoloured day bugs, so they still stand out against the dark
+    pub fn sprite(&self) -> (&'static str, Color) {
+        match self {
+            BugSpecies::OrangeFly => ("sprites/bugs/fly_bug_0.png", Color::WHITE),
+            BugSpecies::BlueFly => ("sprites/bugs/fly_bug_1.png", Color::WHITE),
+            BugSpecies::Crawler => ("sprites/bugs/bug_0.png", Color::WHITE),
+            BugSpecies::Firefly => ("sprites/bugs/fly_bug_0.png", Color::rgb(0.8, 1.6, 0.4)),
+            BugSpecies::Moth => ("sprites/bugs/fly_bug_1.png", Color::rgb(1.3, 1.2, 1.0)),
+        }
+    }
+}
 
 #[derive(Component)]
-struct Bug;
+pub struct Bug(BugSpecies);
+
+#[derive(Event)]
+pub struct BugKilledEvent(pub BugSpecies);
+
+// sent next to BugKilledEvent when the kill was a bullet's, stomps do not count as hits
+#[derive(Event)]
+pub struct BulletHitEvent;
+
+// a bug made it across the screen and took a heart with it
+#[derive(Event)]
+pub struct HeartLostEvent(pub BugSpecies);
 
 #[derive(Component)]
 struct BugCanFly(bool);
 
 #[derive(Resource)]
-struct BugSpawnTimer(Timer);
-
-#[derive(Component)]
-struct BugAnimateTimer(Timer);
+pub struct BugSpawnTimer(Timer);
 
 #[derive(Component)]
 struct BugVeritcalMovement(Timer, f32);
 
-#[derive(Resource)]
-struct BugAtlas(Vec<Handle<TextureAtlas>>);
+// bugs killed this frame, their despawn only lands at the end of it
+#[derive(Resource, Default)]
+struct KilledBugs(HashSet<Entity>);
 
 fn repeating_timer(time: f32) -> Timer {
     Timer::from_seconds(time, TimerMode::Repeating)
@@ -41,141 +124,190 @@ fn repeating_timer(time: f32) -> Timer {
 pub struct BugPlugin;
 impl Plugin for BugPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(BugSpawnTimer(repeating_timer(BUG_SPAWN_RATE)))
-            .insert_resource(BugAtlas(Vec::new()))
-            .add_systems(OnEnter(GameState::Game), init_bug_atlases)
+        app.add_event::<BugKilledEvent>()
+            .add_event::<BulletHitEvent>()
+            .add_event::<HeartLostEvent>()
+            .insert_resource(BugSpawnTimer(Timer::default()))
+            .init_resource::<KilledBugs>()
+            .add_systems(OnEnter(GameState::Game), reset_bug_spawn_timer)
             .add_systems(OnExit(GameState::Game), despawn_all_bugs)
             .add_systems(
                 Update,
                 (
                     move_bug.before(confine_bug),
                     confine_bug,
-                    spawn_bug,
+                    // both draw from GameRng, a fixed order keeps seeded runs the same
+                    spawn_bug
+                        .before(move_bug)
+                        .after(advance_day)
+                        .after(WeatherSet),
                     despawn_bug,
-                    animate_bug,
-                    eat_bullet_bug,
-                    push_down_flightless_bug.run_if(on_event::<WindowResized>()),
+                    // both cats and every bullet can reach the same bug in one frame
+                    eat_bullet_bug.before(stomp_bug),
+                    stomp_bug,
                 )
                     .run_if(in_state(GameState::Game))
                     .run_if(in_state(SimulationState::Running)),
             );
+
+        #[cfg(debug_assertions)]
+        app.add_console_command(
+            "spawn bug",
+            "spawn bug <orange-fly|blue-fly|crawler|firefly|moth> <x> <y>",
+            &[&BUG_SPECIES_NAMES],
+        )
+        .add_systems(
+            Update,
+            run_bug_commands.run_if(on_event::<ConsoleCommandEvent>()),
+        );
     }
 }
 
-fn init_bug_atlases(
-    asset_server: Res<AssetServer>,
-    mut atlas_resource: ResMut<BugAtlas>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
-) {
-    let bug1_atlas = TextureAtlas::from_grid(
-        asset_server.load("sprites/bugs/fly_bug_0.png"),
-        Vec2::new(16.0, 16.0),
-        2,
-        1,
-        None,
-        None,
-    );
-
-    let bug2_atlas = TextureAtlas::from_grid(
-        asset_server.load("sprites/bugs/fly_bug_1.png"),
-        Vec2::new(16.0, 16.0),
-        2,
-        1,
-        None,
-        None,
-    );
-
-    let bug3_atlas = TextureAtlas::from_grid(
-        asset_server.load("sprites/bugs/bug_0.png"),
-        Vec2::new(16.0, 16.0),
-        2,
-        1,
-        None,
-        None,
-    );
-
-    atlas_resource.0.push(texture_atlases.add(bug1_atlas));
-    atlas_resource.0.push(texture_atlases.add(bug2_atlas));
-    atlas_resource.0.push(texture_atlases.add(bug3_atlas));
+pub fn reset_bug_spawn_timer(mut timer: ResMut<BugSpawnTimer>, config: Res<DifficultyConfig>) {
+    timer.0 = repeating_timer(config.bug_spawn_rate);
 }
 
 fn spawn_bug(
     mut commands: Commands,
     mut timer: ResMut<BugSpawnTimer>,
-
-    bug_atlas: Res<BugAtlas>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut rng: ResMut<GameRng>,
+    game_assets: Res<GameAssets>,
+    day_cycle: Res<DayCycle>,
     time: Res<Time>,
+    tuning: Res<Tuning>,
 ) {
     if !timer.0.tick(time.delta()).just_finished() {
         return;
     }
-    let Ok(window) = window_query.get_single() else {
-        return;
+
+    let species_pool = if day_cycle.is_night() {
+        NIGHT_SPECIES
+    } else {
+        DAY_SPECIES
     };
+    let species = species_pool[rng.0.gen_range(0..species_pool.len())];
 
-    let bug_selector = rand::thread_rng().gen_range(0..3);
+    let y = if !species.can_fly() {
+        // crawlers stay on the ground, hence you don't want vertical random offset
+        -ARENA_HEIGHT / 2.0 + SPAWN_HORIZONTAL_PADDING
+    } else {
+        (ARENA_HEIGHT / 2.0 - SPAWN_HORIZONTAL_PADDING)
+            * rng.0.gen::<f32>()
+            * if rng.0.gen::<bool>() { -1.0 } else { 1.0 }
+    };
+    let x = -ARENA_WIDTH / 2.0 - BUG_SIZE / 2.0;
+
+    spawn_bug_at(
+        &mut commands,
+        &game_assets,
+        species,
+        Vec2::new(x, y),
+        &tuning,
+        &mut rng,
+    );
+}
 
+fn spawn_bug_at(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    species: BugSpecies,
+    position: Vec2,
+    tuning: &Tuning,
+    rng: &mut GameRng,
+) {
     let mut bug_sprite = SpriteSheetBundle {
-        texture_atlas: bug_atlas.0[bug_selector].clone(),
-        sprite: TextureAtlasSprite::new(0),
+        texture_atlas: game_assets.bug_atlases[species.index()].clone(),
+        sprite: TextureAtlasSprite {
+            color: species.sprite().1,
+            ..TextureAtlasSprite::new(0)
+        },
         ..default()
     };
+    bug_sprite.transform.translation = position.extend(0.0);
 
-    let transform = &mut bug_sprite.transform.translation;
-
-    if bug_selector == 2 {
-        // CRAWLING BUG == 2, hence you don't want vertical random offset
-        transform.y = -(window.height() / SCALE_FACTOR) / 2.0 + SPAWN_HORIZONTAL_PADDING
-    } else {
-        transform.y = ((window.height() / SCALE_FACTOR) / 2.0 - SPAWN_HORIZONTAL_PADDING)
-            * random::<f32>()
-            * if random::<bool>() { -1.0 } else { 1.0 };
-    }
-    transform.x = -(window.width() / SCALE_FACTOR) / 2.0 - BUG_SIZE / 2.0;
-
-    let vertical_interval = rand::thread_rng().gen_range(BUG_VERTICAL_MOVEMENT_INTERVAL);
+    let (min_interval, max_interval) = tuning.bug_vertical_interval;
+    let vertical_interval = rng.0.gen_range(min_interval..=max_interval);
 
     commands.spawn((
         bug_sprite,
-        Bug,
-        BugAnimateTimer(repeating_timer(BUG_ANIMATION_INTERVAL)),
+        Bug(species),
+        SpriteAnimator::new(game_assets.bug_clips.clone(), species.clip()),
         BugVeritcalMovement(repeating_timer(vertical_interval), 0.0),
-        BugCanFly(bug_selector != 2),
+        BugCanFly(species.can_fly()),
     ));
 }
 
+// spawn bug <species> <x> <y>
+#[cfg(debug_assertions)]
+fn run_bug_commands(
+    mut commands: Commands,
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    mut reply_writer: EventWriter<ConsoleReply>,
+    mut rng: ResMut<GameRng>,
+    game_assets: Res<GameAssets>,
+    tuning: Res<Tuning>,
+) {
+    for command in command_reader.read() {
+        if command.name != "spawn bug" {
+            continue;
+        }
+
+        let species = command.args.first().and_then(|name| {
+            BUG_SPECIES_NAMES
+                .iter()
+                .position(|species_name| species_name == name)
+        });
+        let (Some(bug_selector), Some(x), Some(y)) =
+            (species, command.arg::<f32>(1), command.arg::<f32>(2))
+        else {
+            reply_writer.send(ConsoleReply(format!("usage: {}", command.usage)));
+            continue;
+        };
+        spawn_bug_at(
+            &mut commands,
+            &game_assets,
+            BUG_SPECIES[bug_selector],
+            Vec2::new(x, y),
+            &tuning,
+            &mut rng,
+        );
+    }
+}
+
 fn move_bug(
     mut bug_query: Query<(&mut Transform, &mut BugVeritcalMovement, &BugCanFly), With<Bug>>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
     diff_mult: Res<DifficultyMultiplier>,
+    config: Res<DifficultyConfig>,
+    tuning: Res<Tuning>,
+    weather: Res<CurrentWeather>,
 ) {
-    let mut rng = rand::thread_rng();
     for (mut bug_transform, mut movement, can_fly) in &mut bug_query {
         if movement.0.tick(time.delta()).just_finished() && can_fly.0 {
-            movement.1 = rng.gen_range(-1..=1) as f32;
+            movement.1 = rng.0.gen_range(-1..=1) as f32;
         }
         bug_transform.translation.y +=
-            movement.1 * BUG_VERTICAL_SPEED * time.delta_seconds() * diff_mult.0;
-        bug_transform.translation.x += BUG_SPEED * time.delta_seconds() * diff_mult.0;
+            movement.1 * tuning.bug_vertical_speed * time.delta_seconds() * diff_mult.0;
+        // bugs only ever move right, so they always leave past the side they are despawned on
+        let speed = config.bug_speed * diff_mult.0;
+        let speed = (speed + weather.wind * WIND_SHARE).max(speed * MIN_BUG_SPEED);
+        bug_transform.translation.x += speed * time.delta_seconds();
     }
 }
 
-fn despawn_bug(
+pub fn despawn_bug(
     mut commands: Commands,
     mut hearts: ResMut<Heart>,
-    transform_query: Query<(&Transform, Entity), With<Bug>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut heart_lost_writer: EventWriter<HeartLostEvent>,
+    transform_query: Query<(&Transform, Entity, &Bug)>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
-    for (transform, entity) in &transform_query {
-        if transform.translation.x - (BUG_SIZE / 2.0) > (window.width() / 2.0) / SCALE_FACTOR {
+    for (transform, entity, bug) in &transform_query {
+        if transform.translation.x - (BUG_SIZE / 2.0) > ARENA_WIDTH / 2.0 {
             commands.entity(entity).despawn();
             if hearts.0 > 0 {
-                hearts.0 -= 1
+                hearts.0 -= 1;
+                heart_lost_writer.send(HeartLostEvent(bug.0));
             }
         }
     }
@@ -187,63 +319,89 @@ fn despawn_all_bugs(mut commands: Commands, query: Query<Entity, With<Bug>>) {
     }
 }
 
-fn animate_bug(
-    mut query: Query<(&mut TextureAtlasSprite, &mut BugAnimateTimer), With<Bug>>,
-    time: Res<Time>,
-) {
-    for (mut sprite, mut animation_timer) in &mut query {
-        if !animation_timer.0.tick(time.delta()).just_finished() {
-            continue;
-        }
-        sprite.index = if sprite.index == 0 { 1 } else { 0 };
-    }
-}
-
+#[allow(clippy::too_many_arguments)]
 fn eat_bullet_bug(
     mut commands: Commands,
     mut score: ResMut<Score>,
-    bullet_query: Query<(&Transform, Entity), With<Bullet>>,
-    bug_query: Query<(&Transform, Entity), With<Bug>>,
+    mut player_scores: ResMut<PlayerScores>,
+    mut bug_killed_writer: EventWriter<BugKilledEvent>,
+    mut bullet_hit_writer: EventWriter<BulletHitEvent>,
+    bullet_query: Query<(&Transform, &Bullet, Entity)>,
+    bug_query: Query<(&Transform, Entity, &Bug)>,
+    mut killed: ResMut<KilledBugs>,
 ) {
-    for (bullet_tranform, bullet) in &bullet_query {
-        for (bug_tranform, bug) in &bug_query {
-            if bullet_tranform
-                .translation
-                .distance(bug_tranform.translation)
-                < BUG_SIZE
+    killed.0.clear();
+
+    for (bullet_tranform, bullet_info, bullet) in &bullet_query {
+        for (bug_tranform, bug, bug_info) in &bug_query {
+            if !killed.0.contains(&bug)
+                && bullet_tranform
+                    .translation
+                    .distance(bug_tranform.translation)
+                    < BUG_SIZE
             {
+                killed.0.insert(bug);
                 commands.entity(bug).despawn();
                 commands.entity(bullet).despawn();
+                spawn_emitter(
+                    &mut commands,
+                    BUG_BURST,
+                    bug_tranform.translation.truncate(),
+                    Vec2::X * bullet_info.direction(),
+                );
 
                 score.0 += 5;
+                player_scores.0[bullet_info.owner.index()] += 5;
+                bug_killed_writer.send(BugKilledEvent(bug_info.0));
+                bullet_hit_writer.send(BulletHitEvent);
+                // the bullet is spent on the first bug it hits
+                break;
             }
         }
     }
 }
 
-fn push_down_flightless_bug(
-    mut window_resize: EventReader<WindowResized>,
-    mut query: Query<(&BugCanFly, &mut BugVeritcalMovement), With<Bug>>,
+// a falling cat squashes the bug it lands on and bounces off it
+fn stomp_bug(
+    mut commands: Commands,
+    mut bug_killed_writer: EventWriter<BugKilledEvent>,
+    mut cat_query: Query<(&Transform, &mut Cat)>,
+    bug_query: Query<(&Transform, Entity, &Bug)>,
+    mut killed: ResMut<KilledBugs>,
+    mode: Res<GameMode>,
+    tuning: Res<Tuning>,
 ) {
-    for _ in window_resize.read() {
-        for (can_fly, mut vertical_movement) in &mut query {
-            if can_fly.0 {
-                continue;
+    if !mode.can_stomp() {
+        return;
+    }
+
+    for (cat_transform, mut cat) in &mut cat_query {
+        if !cat.is_falling() {
+            continue;
+        }
+
+        for (bug_transform, bug, bug_info) in &bug_query {
+            let offset = cat_transform.translation - bug_transform.translation;
+            if offset.x.abs() < BUG_SIZE / 2.0 && offset.y > 0.0 && offset.y < BUG_SIZE {
+                if !killed.0.insert(bug) {
+                    continue;
+                }
+                commands.entity(bug).despawn();
+                spawn_emitter(
+                    &mut commands,
+                    BUG_BURST,
+                    bug_transform.translation.truncate(),
+                    Vec2::NEG_Y,
+                );
+                bug_killed_writer.send(BugKilledEvent(bug_info.0));
+                cat.bounce(tuning.cat_stomp_bounce);
             }
-            vertical_movement.1 = -1.0;
         }
     }
 }
 
-fn confine_bug(
-    mut transform_query: Query<(&mut Transform, &mut BugVeritcalMovement), With<Bug>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
-    let (y_min, y_max) = get_min_max(window.height());
+fn confine_bug(mut transform_query: Query<(&mut Transform, &mut BugVeritcalMovement), With<Bug>>) {
+    let (y_min, y_max) = get_min_max(ARENA_HEIGHT);
     // let y_min = y_min + SPAWN_HORIZONTAL_PADDING;
 
     for (mut bug_transform, mut bug_movement) in &mut transform_query {
@@ -261,8 +419,8 @@ fn confine_bug(
 }
 
 // this is not similiar to the get_min_max in cat.rs
-fn get_min_max(window_limit: f32) -> (f32, f32) {
-    let min = SPAWN_HORIZONTAL_PADDING - ((window_limit / 2.0) / SCALE_FACTOR);
-    let max = ((window_limit / 2.0) / SCALE_FACTOR) - BUG_SIZE / 2.0;
+fn get_min_max(arena_limit: f32) -> (f32, f32) {
+    let min = SPAWN_HORIZONTAL_PADDING - arena_limit / 2.0;
+    let max = arena_limit / 2.0 - BUG_SIZE / 2.0;
     (min, max)
 }
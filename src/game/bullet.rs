@@ -1,11 +1,14 @@
 use super::{
     cat::{Cat, CAT_SIZE},
-    GameState, SimulationState, SCALE_FACTOR,
+    particles::{spawn_emitter, MUZZLE_FLASH},
+    tuning::Tuning,
+    weather::{CurrentWeather, WeatherSet},
+    GameState, Player, SimulationState,
 };
-use bevy::{prelude::*, window::PrimaryWindow};
+use crate::{loading::GameAssets, viewport::ARENA_WIDTH};
+use bevy::prelude::*;
 
-const BULLET_SIZE: f32 = 16.0;
-const BULLET_SPEED: f32 = 400.0;
+pub const BULLET_SIZE: f32 = 16.0;
 const BULLET_Y_OFFSET: f32 = 2.5;
 const BULLET_SPARK_DURATION: f32 = 0.02;
 const BULLET_SPARK_X_OFFSET: f32 = 7.5;
@@ -13,16 +16,25 @@ const BULLET_SPARK_X_OFFSET: f32 = 7.5;
 #[derive(Component)]
 pub struct Bullet {
     direction_multiplier: f32,
+    pub owner: Player,
+}
+impl Bullet {
+    // 1 flying right, -1 flying left
+    pub fn direction(&self) -> f32 {
+        self.direction_multiplier
+    }
 }
 
 #[derive(Component)]
 pub struct BulletSpark(Timer);
 
+// direction multiplier and the player whose cat fired
 #[derive(Event)]
-pub struct BulletFireEvent(pub f32);
+pub struct BulletFireEvent(pub f32, pub Player);
 
+// the bullet left the screen without hitting anything
 #[derive(Event)]
-struct DestroyBulletEvent(pub Entity);
+pub struct DestroyBulletEvent(pub Entity);
 
 pub struct BulletPlugin;
 impl Plugin for BulletPlugin {
@@ -34,7 +46,7 @@ impl Plugin for BulletPlugin {
                 Update,
                 (
                     spawn_bullet.run_if(on_event::<BulletFireEvent>()),
-                    move_bullet,
+                    move_bullet.after(WeatherSet),
                     despawn_bullet
                         .run_if(on_event::<DestroyBulletEvent>())
                         .after(move_bullet),
@@ -49,12 +61,15 @@ impl Plugin for BulletPlugin {
 fn spawn_bullet(
     mut commands: Commands,
     mut bullet_fire_reader: EventReader<BulletFireEvent>,
-    cat_query: Query<&Transform, With<Cat>>,
-    asset_server: Res<AssetServer>,
+    cat_query: Query<(&Transform, &Player), With<Cat>>,
+    game_assets: Res<GameAssets>,
 ) {
     for direction_multiplier in bullet_fire_reader.read() {
-        let Ok(cat_transform) = cat_query.get_single() else {
-            return;
+        let Some((cat_transform, _)) = cat_query
+            .iter()
+            .find(|(_, &player)| player == direction_multiplier.1)
+        else {
+            continue;
         };
 
         let mut bullet_transform = *cat_transform;
@@ -63,7 +78,7 @@ fn spawn_bullet(
         bullet_transform.translation.y -= BULLET_Y_OFFSET;
 
         let bullet_sprite_bundle = SpriteBundle {
-            texture: asset_server.load("sprites/bullet/bullet.png"),
+            texture: game_assets.bullet.clone(),
             transform: bullet_transform,
             ..default()
         };
@@ -74,7 +89,7 @@ fn spawn_bullet(
         spark_transform.translation.y -= BULLET_Y_OFFSET;
 
         let bullet_spark_sprite_bundle = SpriteBundle {
-            texture: asset_server.load("sprites/bullet/bullet_trail.png"),
+            texture: game_assets.bullet_trail.clone(),
             transform: spark_transform,
             ..default()
         };
@@ -83,9 +98,17 @@ fn spawn_bullet(
             bullet_sprite_bundle,
             Bullet {
                 direction_multiplier: direction_multiplier.0,
+                owner: direction_multiplier.1,
             },
         ));
 
+        spawn_emitter(
+            &mut commands,
+            MUZZLE_FLASH,
+            spark_transform.translation.truncate(),
+            Vec2::X * direction_multiplier.0,
+        );
+
         commands.spawn((
             bullet_spark_sprite_bundle,
             BulletSpark(Timer::from_seconds(BULLET_SPARK_DURATION, TimerMode::Once)),
@@ -107,24 +130,20 @@ fn despawn_spark(
 
 fn move_bullet(
     mut transform_query: Query<(&mut Transform, &Bullet, Entity)>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
     time: Res<Time>,
+    tuning: Res<Tuning>,
+    weather: Res<CurrentWeather>,
     mut destruction_writter: EventWriter<DestroyBulletEvent>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
     for (mut bullet_transform, bullet, entity) in &mut transform_query {
-        if bullet_transform.translation.x
-            > ((window.width() / 2.0) / SCALE_FACTOR) + BULLET_SIZE / 2.0
-        {
+        if bullet_transform.translation.x.abs() > ARENA_WIDTH / 2.0 + BULLET_SIZE / 2.0 {
             destruction_writter.send(DestroyBulletEvent(entity));
             continue;
         }
 
         bullet_transform.translation.x +=
-            bullet.direction_multiplier * BULLET_SPEED * time.delta_seconds();
+            bullet.direction_multiplier * tuning.bullet_speed * time.delta_seconds();
+        bullet_transform.translation.x += weather.wind * time.delta_seconds();
     }
 }
 
@@ -0,0 +1,234 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    transform::TransformSystem,
+};
+
+use super::{
+    bugs::{BugKilledEvent, HeartLostEvent},
+    GameState,
+};
+use crate::{accessibility::Accessibility, viewport::GameCamera};
+
+const KILL_TRAUMA: f32 = 0.25;
+const HEART_LOST_TRAUMA: f32 = 0.6;
+const TRAUMA_DECAY: f32 = 1.5; // per second
+const MAX_SHAKE_OFFSET: f32 = 4.0; // arena pixels
+const MAX_SHAKE_ANGLE: f32 = 0.05; // radians
+const SHAKE_FREQUENCY: f32 = 25.0;
+const PUNCH_ZOOM: f32 = 0.04; // share of the view the camera closes in by on a kill
+const PUNCH_DURATION: f32 = 0.15;
+const HIT_STOP_DURATION: f32 = 0.05;
+const VIGNETTE_DURATION: f32 = 0.5;
+const VIGNETTE_COLOR: Color = Color::RED;
+const VIGNETTE_SIZE: u32 = 64;
+
+// how hard the screen shakes, the shake grows with its square so small hits stay subtle
+#[derive(Resource, Default)]
+pub struct Trauma(pub f32);
+impl Trauma {
+    pub fn add(&mut self, amount: f32) {
+        self.0 = (self.0 + amount).min(1.0);
+    }
+}
+
+// the camera jumps in on a kill and eases back out, 1 right after the kill
+#[derive(Resource, Default)]
+struct Punch(f32);
+
+// the game is frozen for a moment after a kill, counted in real time
+#[derive(Resource, Default)]
+struct HitStop(f32);
+
+// the game speed without hit-stop, set by the timescale console command
+#[derive(Resource)]
+pub struct TimeScale(pub f32);
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component)]
+struct Vignette(f32); // 1 right after a heart is lost, fading to 0
+
+pub struct CameraEffectsPlugin;
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Trauma>()
+            .init_resource::<Punch>()
+            .init_resource::<HitStop>()
+            .init_resource::<TimeScale>()
+            .add_systems(Startup, spawn_vignette)
+            .add_systems(OnExit(GameState::Game), reset_camera_effects)
+            .add_systems(
+                Update,
+                (
+                    add_trauma,
+                    start_hit_stop.run_if(on_event::<BugKilledEvent>()),
+                    flash_vignette.run_if(on_event::<HeartLostEvent>()),
+                )
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                PostUpdate,
+                (shake_camera, end_hit_stop, fade_vignette)
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(PostUpdate, apply_time_scale.after(end_hit_stop));
+    }
+}
+
+// a white square that is see-through in the middle and solid at the edges, tinted when shown
+fn vignette_image() -> Image {
+    let mut data = Vec::with_capacity((VIGNETTE_SIZE * VIGNETTE_SIZE * 4) as usize);
+    for y in 0..VIGNETTE_SIZE {
+        for x in 0..VIGNETTE_SIZE {
+            let position = Vec2::new(x as f32, y as f32) + 0.5;
+            let from_centre = (position / VIGNETTE_SIZE as f32 - 0.5).abs() * 2.0;
+            let edge = from_centre.max_element().powi(3);
+            data.extend_from_slice(&[255, 255, 255, (edge * 255.0) as u8]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            ..default()
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    // smooth when stretched over the window, unlike the pixel art
+    image.sampler = ImageSampler::linear();
+    image
+}
+
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            image: UiImage::new(images.add(vignette_image())),
+            background_color: Color::NONE.into(),
+            ..default()
+        },
+        Vignette(0.0),
+    ));
+}
+
+fn add_trauma(
+    mut trauma: ResMut<Trauma>,
+    mut punch: ResMut<Punch>,
+    mut killed_reader: EventReader<BugKilledEvent>,
+    mut heart_lost_reader: EventReader<HeartLostEvent>,
+) {
+    for _ in killed_reader.read() {
+        trauma.add(KILL_TRAUMA);
+        punch.0 = 1.0;
+    }
+    for _ in heart_lost_reader.read() {
+        trauma.add(HEART_LOST_TRAUMA);
+    }
+}
+
+fn shake_camera(
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+    mut trauma: ResMut<Trauma>,
+    mut punch: ResMut<Punch>,
+    time: Res<Time<Real>>,
+    accessibility: Res<Accessibility>,
+) {
+    let Ok(mut transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    trauma.0 = (trauma.0 - TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+    punch.0 = (punch.0 - time.delta_seconds() / PUNCH_DURATION).max(0.0);
+    let (shake, zoom) = if accessibility.reduced_motion {
+        (0.0, 0.0)
+    } else {
+        (trauma.0 * trauma.0, punch.0 * punch.0)
+    };
+
+    // out of phase sine waves wander smoothly without needing a noise function
+    let t = time.elapsed_seconds() * SHAKE_FREQUENCY;
+    let offset = Vec2::new((t * 1.1).sin(), (t * 1.7 + 1.3).sin()) * MAX_SHAKE_OFFSET * shake;
+    transform.translation = offset.round().extend(transform.translation.z);
+    transform.rotation = Quat::from_rotation_z((t * 0.9 + 2.1).sin() * MAX_SHAKE_ANGLE * shake);
+    // a smaller camera sees less of the arena, so everything looks closer
+    transform.scale = Vec3::splat(1.0 - PUNCH_ZOOM * zoom);
+}
+
+fn start_hit_stop(mut hit_stop: ResMut<HitStop>, accessibility: Res<Accessibility>) {
+    if accessibility.reduced_motion {
+        return;
+    }
+    hit_stop.0 = HIT_STOP_DURATION;
+}
+
+fn end_hit_stop(mut hit_stop: ResMut<HitStop>, real_time: Res<Time<Real>>) {
+    hit_stop.0 = (hit_stop.0 - real_time.delta_seconds()).max(0.0);
+}
+
+// hit-stop only ever multiplies the time scale, so neither overwrites the other
+fn apply_time_scale(
+    mut time: ResMut<Time<Virtual>>,
+    hit_stop: Res<HitStop>,
+    time_scale: Res<TimeScale>,
+) {
+    let speed = if hit_stop.0 > 0.0 { 0.0 } else { time_scale.0 };
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+fn flash_vignette(mut vignette_query: Query<&mut Vignette>) {
+    for mut vignette in &mut vignette_query {
+        vignette.0 = 1.0;
+    }
+}
+
+fn fade_vignette(
+    mut vignette_query: Query<(&mut Vignette, &mut BackgroundColor)>,
+    time: Res<Time<Real>>,
+) {
+    for (mut vignette, mut background) in &mut vignette_query {
+        if vignette.0 <= 0.0 && background.0.a() == 0.0 {
+            continue;
+        }
+        vignette.0 = (vignette.0 - time.delta_seconds() / VIGNETTE_DURATION).max(0.0);
+        background.0 = VIGNETTE_COLOR.with_a(vignette.0);
+    }
+}
+
+fn reset_camera_effects(
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+    mut vignette_query: Query<(&mut Vignette, &mut BackgroundColor)>,
+    mut trauma: ResMut<Trauma>,
+    mut punch: ResMut<Punch>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    trauma.0 = 0.0;
+    punch.0 = 0.0;
+    hit_stop.0 = 0.0;
+    for mut transform in &mut camera_query {
+        transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+        transform.scale = Vec3::ONE;
+    }
+    for (mut vignette, mut background) in &mut vignette_query {
+        vignette.0 = 0.0;
+        background.0 = Color::NONE;
+    }
+}
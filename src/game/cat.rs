@@ -1,20 +1,40 @@
-use std::hash::Hash;
-
 use super::{
+    animation::{AnimationEvent, AnimationSet, SpriteAnimator},
+    bugs::HeartLostEvent,
     bullet::BulletFireEvent,
-    controlls::{Controlls, CurrentGamepad},
+    controlls::{CatAction, CatInput},
     ground::{Ground, GroundBuildEvent, GROUND_HEIGHT, GROUND_WIDTH},
-    EntityDirection, GameState, SimulationState, FRICTION, GRAVITY, SCALE_FACTOR,
+    modes::GameMode,
+    particles::{spawn_emitter, FOOTSTEP_DUST, LANDING_DUST},
+    tuning::Tuning,
+    weather::{CurrentWeather, WeatherSet},
+    CoopMode, EntityDirection, GameState, Player, SimulationState,
 };
-use bevy::{prelude::*, window::PrimaryWindow};
+use crate::accessibility::{Accessibility, Theme};
+use crate::loading::GameAssets;
+use crate::viewport::{ARENA_HEIGHT, ARENA_WIDTH};
+use bevy::prelude::*;
+
+#[cfg(debug_assertions)]
+use super::{bugs::despawn_bug, game_over, Heart};
+#[cfg(debug_assertions)]
+use crate::console::{AddConsoleCommand, ConsoleCommandEvent, ConsoleReply};
 
 pub const CAT_SIZE: f32 = 16.0;
-const CAT_SPEEED: f32 = 25.0;
-const CAT_JUMP_FORCE: f32 = 80.0;
-const CAT_BULLET_ANIMATION_DURATION: f32 = 0.12;
+const CAT_FIRE_COOLDOWN: f32 = 0.12;
 const MAX_COLLISION_RADIUS: f32 = 1.5;
-const CAT_GUN_WEIGHT: f32 = 10.0; // subtracts from jump force when gun is equiped
-const ANALOGUE_DEADZONE: f32 = 0.15;
+const PLAYER_SPAWN_GAP: f32 = CAT_SIZE * 2.0;
+const LANDING_DUST_SPEED: f32 = 40.0;
+const RUN_SPEED: f32 = 10.0; // slower than this on the ground counts as standing still
+const WIND_SHARE: f32 = 0.5; // of the wind speed a cat in the air drifts with
+
+#[derive(Event)]
+pub struct CatJumpEvent;
+
+// escaped bugs cost no hearts, toggled from the console
+#[cfg(debug_assertions)]
+#[derive(Resource, Default)]
+struct GodMode(bool);
 
 #[derive(Component)]
 pub struct Cat {
@@ -22,359 +42,456 @@ pub struct Cat {
     direction: EntityDirection,
     can_jump: bool,
     has_gun: bool,
-    is_firing: bool,
+    fire_timer: Timer, // the gun can fire again once it finishes
 }
 impl Cat {
+    pub fn is_falling(&self) -> bool {
+        self.velocity.y < 0.0
+    }
+
+    pub fn bounce(&mut self, force: f32) {
+        self.velocity.y = force;
+    }
+
     fn new() -> Self {
         Self {
             velocity: Vec3::ZERO,
             direction: EntityDirection::Right,
             can_jump: false,
             has_gun: false,
-            is_firing: false,
+            fire_timer: Timer::from_seconds(CAT_FIRE_COOLDOWN, TimerMode::Once),
         }
     }
 }
 
-#[derive(Resource)]
-struct CatBulletFireTimer(Timer);
+// what the cat looks like it is doing, each state plays the clip of the same name
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq)]
+enum CatAnimation {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Fire,
+    Hurt,
+}
+impl CatAnimation {
+    fn clip(&self, armed: bool) -> String {
+        let name = match self {
+            CatAnimation::Idle => "idle",
+            CatAnimation::Run => "run",
+            CatAnimation::Jump => "jump",
+            CatAnimation::Fall => "fall",
+            CatAnimation::Fire => "fire",
+            CatAnimation::Hurt => "hurt",
+        };
+        if armed {
+            format!("{}-armed", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    // played through once before the cat's movement picks the state again
+    fn is_one_shot(&self) -> bool {
+        matches!(self, CatAnimation::Fire | CatAnimation::Hurt)
+    }
+}
 
 pub struct CatPlugin;
 impl Plugin for CatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Game), spawn_cat)
-            .insert_resource(CatBulletFireTimer(Timer::from_seconds(
-                CAT_BULLET_ANIMATION_DURATION,
-                TimerMode::Once,
-            )))
+        app.add_event::<CatJumpEvent>()
+            .add_systems(OnEnter(GameState::Game), spawn_cat)
             .add_systems(
                 Update,
                 (
-                    (
-                        move_cat::<KeyCode>.before(confine_cat),
-                        toggle_cat_gun::<KeyCode>,
-                        fire_bullet_cat::<KeyCode>,
-                        build_ground_cat::<KeyCode>,
-                        jump_cat::<KeyCode>,
-                    ),
-                    (
-                        move_cat::<GamepadButton>.before(confine_cat),
-                        toggle_cat_gun::<GamepadButton>,
-                        fire_bullet_cat::<GamepadButton>,
-                        build_ground_cat::<GamepadButton>,
-                        jump_cat::<GamepadButton>,
-                    ),
-                    (
-                        fire_bullet_cat::<MouseButton>,
-                        toggle_cat_gun::<MouseButton>,
-                    ),
-                    analogue_movement,
-                    physics_on_cat,
+                    move_cat.before(confine_cat),
+                    toggle_cat_gun,
+                    fire_bullet_cat,
+                    build_ground_cat,
+                    jump_cat,
+                    physics_on_cat.after(WeatherSet),
                     confine_cat,
-                    animate_cat,
+                    animate_cat
+                        .after(fire_bullet_cat)
+                        .after(confine_cat)
+                        .before(AnimationSet),
+                    kick_up_dust.after(AnimationSet),
                 )
                     .run_if(in_state(SimulationState::Running))
                     .run_if(in_state(GameState::Game)),
             )
+            .add_systems(
+                Update,
+                tint_player_two
+                    .run_if(resource_changed::<Theme>())
+                    .run_if(in_state(GameState::Game)),
+            )
             .add_systems(OnExit(GameState::Game), despawn_cat);
+
+        #[cfg(debug_assertions)]
+        app.init_resource::<GodMode>()
+            .add_console_command("god", "god <on|off>", &[&["on", "off"]])
+            .add_console_command("give gun", "give gun", &[])
+            .add_systems(
+                Update,
+                (
+                    run_cat_commands.run_if(on_event::<ConsoleCommandEvent>()),
+                    refill_hearts_in_god_mode
+                        .after(despawn_bug)
+                        .before(game_over)
+                        .run_if(on_event::<HeartLostEvent>()),
+                ),
+            );
     }
 }
 
 fn spawn_cat(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    game_assets: Res<GameAssets>,
+    coop_mode: Res<CoopMode>,
+    theme: Res<Theme>,
 ) {
-    let texture_handle = asset_server.load("sprites/cat/cat_sheet_2.png");
-    let atlas = TextureAtlas::from_grid(
-        texture_handle,
-        Vec2::new(16.0, 16.0),
-        // columns, rows, padding, offset
-        5,
-        1,
-        None,
-        None,
-    );
-
-    let atlas_handle = texture_atlases.add(atlas);
-
-    let cat_bundle = SpriteSheetBundle {
-        texture_atlas: atlas_handle,
-        sprite: TextureAtlasSprite::new(1),
-        ..default()
-    };
-
-    commands.spawn((cat_bundle, Cat::new()));
+    for &player in coop_mode.players() {
+        let mut cat_bundle = SpriteSheetBundle {
+            texture_atlas: game_assets.cat_atlas.clone(),
+            sprite: TextureAtlasSprite::new(1),
+            ..default()
+        };
+
+        if player == Player::Two {
+            cat_bundle.sprite.color = theme.player_two_tint;
+            cat_bundle.transform.translation.x += PLAYER_SPAWN_GAP;
+        }
+
+        commands.spawn((
+            cat_bundle,
+            Cat::new(),
+            CatAnimation::default(),
+            SpriteAnimator::new(
+                game_assets.cat_clips.clone(),
+                &CatAnimation::default().clip(false),
+            ),
+            CatInput::default(),
+            player,
+        ));
+    }
 }
 
-fn despawn_cat(mut commands: Commands, cat_query: Query<Entity, With<Cat>>) {
-    let Ok(entity) = cat_query.get_single() else {
-        return;
-    };
-    commands.entity(entity).despawn();
+fn tint_player_two(mut cat_query: Query<(&mut TextureAtlasSprite, &Player)>, theme: Res<Theme>) {
+    for (mut sprite, &player) in &mut cat_query {
+        if player == Player::Two {
+            sprite.color = theme.player_two_tint;
+        }
+    }
 }
 
-fn move_cat<T: Copy + Eq + Hash + Send + Sync + 'static>(
-    mut transform_query: Query<&mut Cat>,
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
-    // gamepad_controller: Res<Controlls<GamepadButton>>,
-) {
-    let Ok(mut cat) = transform_query.get_single_mut() else {
-        return;
-    };
+fn despawn_cat(mut commands: Commands, cat_query: Query<Entity, With<Cat>>) {
+    for entity in &cat_query {
+        commands.entity(entity).despawn();
+    }
+}
 
-    if let Some(keypress) = controller.right {
-        if input.pressed(keypress) {
+fn move_cat(mut cat_query: Query<(&mut Cat, &CatInput)>, tuning: Res<Tuning>) {
+    for (mut cat, cat_input) in &mut cat_query {
+        if cat_input.pressed(CatAction::Right) {
             cat.direction = EntityDirection::Right;
-            cat.velocity.x += CAT_SPEEED;
+            cat.velocity.x += tuning.cat_speed;
+        }
+
+        if cat_input.pressed(CatAction::Left) {
+            cat.direction = EntityDirection::Left;
+            cat.velocity.x -= tuning.cat_speed;
         }
-    }
 
-    if let Some(keypress) = controller.left {
-        if input.pressed(keypress) {
+        if cat_input.analogue > 0.0 {
+            cat.direction = EntityDirection::Right;
+        } else if cat_input.analogue < 0.0 {
             cat.direction = EntityDirection::Left;
-            cat.velocity.x -= CAT_SPEEED;
         }
+        cat.velocity.x += tuning.cat_speed * cat_input.analogue;
     }
 }
 
-fn analogue_movement(
-    mut transform_query: Query<&mut Cat>,
-    current: Res<CurrentGamepad>,
-    axes: Res<Axis<GamepadAxis>>,
+fn physics_on_cat(
+    mut cat_query: Query<(&mut Transform, &mut Cat)>,
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    weather: Res<CurrentWeather>,
 ) {
-    let Ok(mut cat) = transform_query.get_single_mut() else {
-        return;
-    };
-
-    if let Some(id) = current.0 {
-        let leftaxis_x = GamepadAxis::new(id, GamepadAxisType::LeftStickX);
-        let leftaxis_y = GamepadAxis::new(id, GamepadAxisType::LeftStickY);
-
-        if let (Some(x), Some(y)) = (axes.get(leftaxis_x), axes.get(leftaxis_y)) {
-            let leftaxis = Vec2::new(x, y);
-
-            if leftaxis.length() > ANALOGUE_DEADZONE && leftaxis.x > ANALOGUE_DEADZONE {
-                cat.direction = EntityDirection::Right;
-                cat.velocity.x += CAT_SPEEED * leftaxis.length();
-            }
+    for (mut transform, mut cat) in &mut cat_query {
+        cat.velocity.y -= tuning.gravity * time.delta_seconds();
+
+        // FRICTION, wet ground is slippery
+        let friction = if cat.can_jump {
+            weather.ground_friction(&tuning)
+        } else {
+            tuning.friction
+        };
+        cat.velocity.x -= cat.velocity.x * (1.0 - friction);
 
-            if leftaxis.length() > ANALOGUE_DEADZONE && leftaxis.x < ANALOGUE_DEADZONE {
-                cat.direction = EntityDirection::Left;
-                cat.velocity.x -= CAT_SPEEED * leftaxis.length();
-            }
+        transform.translation += cat.velocity * time.delta_seconds();
+        if !cat.can_jump {
+            transform.translation.x += weather.wind * WIND_SHARE * time.delta_seconds();
         }
     }
 }
 
-fn physics_on_cat(mut cat_query: Query<(&mut Transform, &mut Cat), With<Cat>>, time: Res<Time>) {
-    let Ok((mut transform, mut cat)) = cat_query.get_single_mut() else {
-        return;
-    };
-    cat.velocity.y -= GRAVITY * time.delta_seconds();
-
-    // FRICTION
-    cat.velocity.x -= cat.velocity.x * (1.0 - FRICTION);
-
-    transform.translation += cat.velocity * time.delta_seconds();
-}
-
 fn confine_cat(
+    mut commands: Commands,
     mut transform_query: Query<(&mut Transform, &mut Cat), Without<Ground>>,
     ground_query: Query<&Transform, With<Ground>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
     time: Res<Time>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-    let Ok((mut cat_transform, mut cat)) = transform_query.get_single_mut() else {
-        return;
-    };
+    let (y_min, y_max) = get_min_max(ARENA_HEIGHT);
+    let (x_min, x_max) = get_min_max(ARENA_WIDTH);
 
-    // println!("{:?}", (window.height(), window.width()));
-    // window default: 1280, 720
+    for (mut cat_transform, mut cat) in &mut transform_query {
+        cat_transform.translation += cat.velocity * time.delta_seconds();
+        let fall_speed = -cat.velocity.y;
+        let mut landed = false;
 
-    let (y_min, y_max) = get_min_max(window.height());
-    let (x_min, x_max) = get_min_max(window.width());
+        if cat_transform.translation.y < y_min {
+            cat_transform.translation.y = y_min;
+            cat.velocity.y = 0.0;
+            cat.can_jump = true;
+            landed = true;
+        }
 
-    cat_transform.translation += cat.velocity * time.delta_seconds();
+        if cat_transform.translation.y > y_max {
+            cat_transform.translation.y = y_max
+        }
 
-    if cat_transform.translation.y < y_min {
-        cat_transform.translation.y = y_min;
-        cat.velocity.y = 0.0;
-        cat.can_jump = true;
-    }
+        if cat_transform.translation.x < x_min {
+            cat_transform.translation.x = x_min
+        }
+        if cat_transform.translation.x > x_max {
+            cat_transform.translation.x = x_max
+        }
 
-    if cat_transform.translation.y > y_max {
-        cat_transform.translation.y = y_max
-    }
+        // ground_collision
+        for ground_transfrom in &ground_query {
+            let mut ground_top = ground_transfrom.translation;
+            ground_top.y += GROUND_WIDTH / 2.0;
 
-    if cat_transform.translation.x < x_min {
-        cat_transform.translation.x = x_min
-    }
-    if cat_transform.translation.x > x_max {
-        cat_transform.translation.x = x_max
-    }
+            let mut cat_bottom = cat_transform.translation;
+            cat_bottom.y -= CAT_SIZE / 2.0;
 
-    // ground_collision
-    for ground_transfrom in &ground_query {
-        let mut ground_top = ground_transfrom.translation;
-        ground_top.y += GROUND_WIDTH / 2.0;
+            if !(cat_bottom.x + CAT_SIZE / 2.0 >= ground_top.x - (GROUND_WIDTH / 2.0)
+                && cat_bottom.x - CAT_SIZE <= ground_top.x + (GROUND_WIDTH / 2.0))
+            {
+                continue;
+            }
+            if cat_bottom.distance(ground_top) > GROUND_HEIGHT * MAX_COLLISION_RADIUS {
+                continue;
+            }
 
-        let mut cat_bottom = cat_transform.translation;
-        cat_bottom.y -= CAT_SIZE / 2.0;
+            let ground_limit = ground_transfrom.translation.y + GROUND_WIDTH;
 
-        if !(cat_bottom.x + CAT_SIZE / 2.0 >= ground_top.x - (GROUND_WIDTH / 2.0)
-            && cat_bottom.x - CAT_SIZE <= ground_top.x + (GROUND_WIDTH / 2.0))
-        {
-            continue;
-        }
-        if cat_bottom.distance(ground_top) > GROUND_HEIGHT * MAX_COLLISION_RADIUS {
-            continue;
+            if cat_transform.translation.y < ground_limit {
+                cat_transform.translation.y = ground_limit;
+                cat.velocity.y = 0.0;
+                cat.can_jump = true;
+                landed = true;
+            }
         }
 
-        let ground_limit = ground_transfrom.translation.y + GROUND_WIDTH;
-
-        if cat_transform.translation.y < ground_limit {
-            cat_transform.translation.y = ground_limit;
-            cat.velocity.y = 0.0;
-            cat.can_jump = true;
+        // standing still lands every frame too, only real falls kick up dust
+        if landed && fall_speed > LANDING_DUST_SPEED {
+            let feet = cat_transform.translation.truncate() - Vec2::new(0.0, CAT_SIZE / 2.0);
+            spawn_emitter(&mut commands, LANDING_DUST, feet, Vec2::Y);
         }
     }
 }
 
-fn get_min_max(window_limit: f32) -> (f32, f32) {
-    let min = CAT_SIZE / 2.0 - ((window_limit / 2.0) / SCALE_FACTOR);
-    let max = ((window_limit / 2.0) / SCALE_FACTOR) - CAT_SIZE / 2.0;
+pub fn get_min_max(arena_limit: f32) -> (f32, f32) {
+    let min = CAT_SIZE / 2.0 - arena_limit / 2.0;
+    let max = arena_limit / 2.0 - CAT_SIZE / 2.0;
     (min, max)
 }
 
-fn jump_cat<T: Copy + Eq + Hash + Send + Sync + 'static>(
-    mut cat_query: Query<&mut Cat>,
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
+fn jump_cat(
+    mut cat_query: Query<(&mut Cat, &CatInput)>,
+    mut jump_writer: EventWriter<CatJumpEvent>,
+    tuning: Res<Tuning>,
 ) {
-    let Ok(mut cat) = cat_query.get_single_mut() else {
-        return;
-    };
+    for (mut cat, cat_input) in &mut cat_query {
+        let jump_the_cat =
+            cat_input.just_pressed(CatAction::Jump) || cat_input.just_pressed(CatAction::Up);
+
+        if jump_the_cat && cat.can_jump {
+            cat.velocity.y += tuning.cat_jump_force;
+            if cat.has_gun {
+                cat.velocity.y -= tuning.cat_gun_weight;
+            }
+            cat.can_jump = false;
+            jump_writer.send(CatJumpEvent);
+        }
+    }
+}
 
-    let mut jump_the_cat = false;
+fn animate_cat(
+    mut cat_query: Query<(
+        &mut Transform,
+        &mut CatAnimation,
+        &mut SpriteAnimator,
+        &Cat,
+        &Player,
+    )>,
+    mut fire_reader: EventReader<BulletFireEvent>,
+    mut heart_lost_reader: EventReader<HeartLostEvent>,
+) {
+    let fired: Vec<Player> = fire_reader.read().map(|event| event.1).collect();
+    // every cat shares the hearts, so every cat flinches
+    let hurt = heart_lost_reader.read().count() > 0;
+
+    for (mut transform, mut animation, mut animator, cat, player) in &mut cat_query {
+        match cat.direction {
+            EntityDirection::Left => {
+                transform.rotation = Quat::from_rotation_y(std::f32::consts::PI)
+            }
 
-    if let Some(keypress) = controller.jump {
-        if input.just_pressed(keypress) && cat.can_jump {
-            jump_the_cat = true;
+            EntityDirection::Right => transform.rotation = Quat::default(),
         }
-    };
 
-    if let Some(keypress) = controller.up {
-        if input.just_pressed(keypress) && cat.can_jump {
-            jump_the_cat = true;
-        }
-    };
+        let triggered = if hurt {
+            Some(CatAnimation::Hurt)
+        } else if fired.contains(player) {
+            Some(CatAnimation::Fire)
+        } else {
+            None
+        };
+
+        let next = if let Some(one_shot) = triggered {
+            one_shot
+        } else if animation.is_one_shot() && !animator.finished() {
+            *animation
+        } else if !cat.can_jump {
+            if cat.velocity.y > 0.0 {
+                CatAnimation::Jump
+            } else {
+                CatAnimation::Fall
+            }
+        } else if cat.velocity.x.abs() > RUN_SPEED {
+            CatAnimation::Run
+        } else {
+            CatAnimation::Idle
+        };
 
-    if jump_the_cat {
-        cat.velocity.y += CAT_JUMP_FORCE;
-        if cat.has_gun {
-            cat.velocity.y -= CAT_GUN_WEIGHT;
+        // firing again mid shot starts the shot over
+        if triggered.is_some() {
+            animator.restart();
         }
-        cat.can_jump = false;
+        animator.play(&next.clip(cat.has_gun));
+        *animation = next;
     }
 }
 
-fn animate_cat(mut transform_query: Query<(&mut Transform, &Cat, &mut TextureAtlasSprite)>) {
-    let Ok((mut transform, cat, mut sprite)) = transform_query.get_single_mut() else {
-        return;
-    };
-
-    match cat.direction {
-        EntityDirection::Left => transform.rotation = Quat::from_rotation_y(std::f32::consts::PI),
-
-        EntityDirection::Right => transform.rotation = Quat::default(),
-    }
+// the run clip marks the frames a paw hits the ground
+fn kick_up_dust(
+    mut commands: Commands,
+    mut animation_reader: EventReader<AnimationEvent>,
+    cat_query: Query<&Transform, With<Cat>>,
+) {
+    for event in animation_reader.read() {
+        if event.name != "step" {
+            continue;
+        }
+        let Ok(transform) = cat_query.get(event.entity) else {
+            continue;
+        };
 
-    if cat.can_jump {
-        sprite.index = if cat.has_gun { 2 } else { 0 };
-    } else {
-        sprite.index = 1 + if cat.has_gun { 2 } else { 0 };
+        let feet = transform.translation.truncate() - Vec2::new(0.0, CAT_SIZE / 2.0);
+        spawn_emitter(&mut commands, FOOTSTEP_DUST, feet, Vec2::Y);
     }
+}
 
-    if cat.is_firing && cat.has_gun {
-        sprite.index = 4;
+#[cfg(debug_assertions)]
+fn run_cat_commands(
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    mut reply_writer: EventWriter<ConsoleReply>,
+    mut cat_query: Query<&mut Cat>,
+    mut god_mode: ResMut<GodMode>,
+) {
+    for command in command_reader.read() {
+        match command.name {
+            "god" => match command.args.first().map(String::as_str) {
+                Some("on") => god_mode.0 = true,
+                Some("off") => god_mode.0 = false,
+                _ => reply_writer.send(ConsoleReply(format!("usage: {}", command.usage))),
+            },
+            "give gun" => {
+                for mut cat in &mut cat_query {
+                    cat.has_gun = true;
+                }
+            }
+            _ => (),
+        }
     }
 }
 
-fn toggle_cat_gun<T: Copy + Eq + Send + Sync + 'static + Hash>(
-    mut cat_query: Query<&mut Cat>,
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
+// puts back the hearts despawn_bug took before game_over sees them gone
+#[cfg(debug_assertions)]
+fn refill_hearts_in_god_mode(
+    mut heart_lost_reader: EventReader<HeartLostEvent>,
+    mut hearts: ResMut<Heart>,
+    god_mode: Res<GodMode>,
 ) {
-    let Ok(mut cat) = cat_query.get_single_mut() else {
-        return;
-    };
+    let lost = heart_lost_reader.read().count() as u8;
+    if god_mode.0 {
+        hearts.0 += lost;
+    }
+}
 
-    let Some(keypress) = controller.toggle_weapon else {
+fn toggle_cat_gun(mut cat_query: Query<(&mut Cat, &CatInput)>, mode: Res<GameMode>) {
+    if !mode.has_gun() {
         return;
-    };
+    }
 
-    if input.just_pressed(keypress) {
-        cat.has_gun = !cat.has_gun
+    for (mut cat, cat_input) in &mut cat_query {
+        if cat_input.just_pressed(CatAction::ToggleWeapon) {
+            cat.has_gun = !cat.has_gun
+        }
     }
 }
 
-fn fire_bullet_cat<T: Copy + Eq + Send + Sync + 'static + Hash>(
-    mut cat_query: Query<&mut Cat>,
-    mut anim_time: ResMut<CatBulletFireTimer>,
+fn fire_bullet_cat(
+    mut cat_query: Query<(&mut Cat, &CatInput, &Player)>,
     mut bullet_fire_writer: EventWriter<BulletFireEvent>,
-
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
     time: Res<Time>,
+    accessibility: Res<Accessibility>,
 ) {
-    let Ok(mut cat) = cat_query.get_single_mut() else {
-        return;
-    };
-
-    if !cat.has_gun {
-        return;
-    }
+    for (mut cat, cat_input, &player) in &mut cat_query {
+        if !cat.has_gun {
+            continue;
+        }
 
-    if anim_time.0.tick(time.delta()).just_finished() {
-        cat.is_firing = false;
-    }
+        cat.fire_timer.tick(time.delta());
 
-    let Some(keypress) = controller.fire else {
-        return;
-    };
+        // with auto fire, holding the button shoots whenever the gun is ready
+        let wants_to_fire = cat_input.just_pressed(CatAction::Fire)
+            || (accessibility.auto_fire && cat_input.pressed(CatAction::Fire));
 
-    if input.just_pressed(keypress) && anim_time.0.finished() {
-        let direction_multiplier = match cat.direction {
-            EntityDirection::Right => 1.0,
-            EntityDirection::Left => -1.0,
-        };
+        if wants_to_fire && cat.fire_timer.finished() {
+            let direction_multiplier = match cat.direction {
+                EntityDirection::Right => 1.0,
+                EntityDirection::Left => -1.0,
+            };
 
-        bullet_fire_writer.send(BulletFireEvent(direction_multiplier));
-        anim_time.0.reset();
-        cat.is_firing = true;
+            bullet_fire_writer.send(BulletFireEvent(direction_multiplier, player));
+            cat.fire_timer.reset();
+        }
     }
 }
 
-fn build_ground_cat<T: Copy + Eq + Send + Sync + 'static + Hash>(
+fn build_ground_cat(
     mut ground_build_writer: EventWriter<GroundBuildEvent>,
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
-    transform_query: Query<&Transform, With<Cat>>,
+    cat_query: Query<(&Transform, &CatInput), With<Cat>>,
 ) {
-    let Ok(transform) = transform_query.get_single() else {
-        return;
-    };
-
-    let Some(keypress) = controller.place_block else {
-        return;
-    };
-
-    if input.just_pressed(keypress) {
-        ground_build_writer.send(GroundBuildEvent(transform.translation));
+    for (transform, cat_input) in &cat_query {
+        if cat_input.just_pressed(CatAction::PlaceBlock) {
+            ground_build_writer.send(GroundBuildEvent(transform.translation));
+        }
     }
 }
@@ -1,130 +1,124 @@
-use super::{GameState, SCALE_FACTOR};
-use bevy::{prelude::*, window::PrimaryWindow};
+use super::{tuning::Tuning, weather::CurrentWeather, GameState};
+use crate::accessibility::Accessibility;
+use crate::loading::GameAssets;
+use crate::viewport::{ARENA_HEIGHT, ARENA_WIDTH};
+use bevy::prelude::*;
 use rand::{random, Rng};
 
 const CLOUD_SIZE: f32 = 16.0;
-const CLOUD_SPAWN_RATE: f32 = 0.69;
-const CLOUD_SPEED: f32 = 15.0;
 const INITIAL_CLOUDS: usize = 30;
+const MIN_CLOUD_SPEED: f32 = 0.25; // share of a cloud's speed it keeps against any headwind
 
 #[derive(Component)]
-struct Cloud {
+pub struct Cloud {
     speed: f32,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 struct CloudTimer(Timer);
-impl CloudTimer {
-    fn new() -> Self {
-        Self(Timer::from_seconds(CLOUD_SPAWN_RATE, TimerMode::Repeating))
-    }
-}
 
 pub struct CloudPlugin;
 impl Plugin for CloudPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(CloudTimer::new())
+        app.init_resource::<CloudTimer>()
             .add_systems(OnEnter(GameState::Game), spawn_initial_clouds)
             .add_systems(
                 Update,
                 (move_clouds, spawn_new_clouds, despawn_outbound_cloud)
                     .run_if(in_state(GameState::Game)),
             )
+            .add_systems(
+                Update,
+                retime_cloud_spawns.run_if(resource_changed::<Tuning>()),
+            )
             .add_systems(OnExit(GameState::Game), despawn_clouds);
     }
 }
-fn spawn_initial_clouds(
-    mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    asset_server: Res<AssetServer>,
-) {
+fn spawn_initial_clouds(mut commands: Commands, game_assets: Res<GameAssets>, tuning: Res<Tuning>) {
     let mut random_number = rand::thread_rng();
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
 
     for _ in 0..INITIAL_CLOUDS {
-        let random_texture = format!(
-            "sprites/clouds/cloud_{}.png",
-            random_number.gen_range(1..=3)
-        );
+        let random_texture = random_number.gen_range(0..game_assets.clouds.len());
         let mut cloud_sprite = SpriteBundle {
-            texture: asset_server.load(random_texture),
+            texture: game_assets.clouds[random_texture].clone(),
             ..default()
         };
 
-        cloud_sprite.transform.translation.y +=
-            (window.height() / 2.0) / SCALE_FACTOR * random::<f32>();
-        cloud_sprite.transform.translation.x = ((window.width() / 2.0) / SCALE_FACTOR
-            * random::<f32>())
-            * if random::<bool>() { 1.0 } else { -1.0 };
+        cloud_sprite.transform.translation.y += ARENA_HEIGHT / 2.0 * random::<f32>();
+        cloud_sprite.transform.translation.x =
+            (ARENA_WIDTH / 2.0 * random::<f32>()) * if random::<bool>() { 1.0 } else { -1.0 };
 
         commands.spawn((
             cloud_sprite,
             Cloud {
-                speed: (0.5 + random::<f32>() % 0.5) * CLOUD_SPEED,
+                speed: (0.5 + random::<f32>() % 0.5) * tuning.cloud_speed,
             },
         ));
     }
 }
 
+fn retime_cloud_spawns(mut spawn_timer: ResMut<CloudTimer>, tuning: Res<Tuning>) {
+    spawn_timer.0 = Timer::from_seconds(tuning.cloud_spawn_rate, TimerMode::Repeating);
+}
+
 fn spawn_new_clouds(
     mut commands: Commands,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
     time: Res<Time>,
     mut spawn_timer: ResMut<CloudTimer>,
+    tuning: Res<Tuning>,
+    accessibility: Res<Accessibility>,
 ) {
+    // standing clouds would never drift off to be despawned
+    if accessibility.reduced_motion {
+        return;
+    }
     if !spawn_timer.0.tick(time.delta()).just_finished() {
         return;
     }
 
     let mut random_number = rand::thread_rng();
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
 
-    let random_texture = format!(
-        "sprites/clouds/cloud_{}.png",
-        random_number.gen_range(1..=3)
-    );
+    let random_texture = random_number.gen_range(0..game_assets.clouds.len());
     let mut cloud_sprite = SpriteBundle {
-        texture: asset_server.load(random_texture),
+        texture: game_assets.clouds[random_texture].clone(),
         ..default()
     };
 
-    cloud_sprite.transform.translation.y +=
-        (window.height() / 2.0) / SCALE_FACTOR * random::<f32>();
-    cloud_sprite.transform.translation.x +=
-        (window.width() / 2.0) / SCALE_FACTOR + CLOUD_SIZE / 2.0;
+    cloud_sprite.transform.translation.y += ARENA_HEIGHT / 2.0 * random::<f32>();
+    cloud_sprite.transform.translation.x += ARENA_WIDTH / 2.0 + CLOUD_SIZE / 2.0;
 
     commands.spawn((
         cloud_sprite,
         Cloud {
-            speed: (0.5 + random::<f32>() % 0.5) * CLOUD_SPEED,
+            speed: (0.5 + random::<f32>() % 0.5) * tuning.cloud_speed,
         },
     ));
 }
 
-fn move_clouds(mut transform_query: Query<(&mut Transform, &Cloud)>, time: Res<Time>) {
+fn move_clouds(
+    mut transform_query: Query<(&mut Transform, &Cloud)>,
+    time: Res<Time>,
+    weather: Res<CurrentWeather>,
+    accessibility: Res<Accessibility>,
+) {
+    if accessibility.reduced_motion {
+        return;
+    }
+
+    // clouds only ever drift left, so they always leave past the side they are despawned on
     for (mut transform, cloud) in &mut transform_query {
-        transform.translation.x -= cloud.speed * time.delta_seconds();
+        let speed = (cloud.speed - weather.wind).max(cloud.speed * MIN_CLOUD_SPEED);
+        transform.translation.x -= speed * time.delta_seconds();
     }
 }
 
 fn despawn_outbound_cloud(
     mut commands: Commands,
     cloud_query: Query<(Entity, &Transform), With<Cloud>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
     for (entity, transform) in &cloud_query {
-        if transform.translation.x
-            < -(window.width() / 2.0) / SCALE_FACTOR - (CLOUD_SIZE / SCALE_FACTOR) / 2.0
-        {
+        if transform.translation.x < -ARENA_WIDTH / 2.0 - CLOUD_SIZE / 2.0 {
             commands.entity(entity).despawn()
         }
     }
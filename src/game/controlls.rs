@@ -1,14 +1,141 @@
-use std::hash::Hash;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
 
-use bevy::input::gamepad::GamepadButtonInput;
+use bevy::input::gamepad::{GamepadButtonInput, GamepadConnection, GamepadEvent};
 use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputSystem};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{CoopMode, Player};
+use crate::locale::Locale;
+use crate::menu::toast::ToastEvent;
+use crate::{GameState, SimulationState};
+
+const CONTROLL_CHANGE_TIMEOUT: f32 = 5.0;
+const ANALOGUE_DEADZONE: f32 = 0.15;
+pub const CANCEL_KEY: KeyCode = KeyCode::Escape;
+pub const CANCEL_BUTTON: GamepadButtonType = GamepadButtonType::Select;
 
 #[derive(Resource)]
 pub struct CurrentGamepad(pub Option<Gamepad>);
 
+// fills every cat's CatInput from the bound devices before Update runs
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CatInputSet;
+
+// waiting for the player to press the new button for an action
 #[derive(Resource)]
-pub struct ControllChange(pub CatAction);
+pub struct ControllChange(pub CatAction, pub Timer);
+impl ControllChange {
+    pub fn new(action: CatAction) -> Self {
+        Self(
+            action,
+            Timer::from_seconds(CONTROLL_CHANGE_TIMEOUT, TimerMode::Once),
+        )
+    }
+}
+
+// the pressed button is already used by another action,
+// nothing changes until the player resolves it
+#[derive(Resource)]
+pub struct ControllConflict<T> {
+    pub action: CatAction,
+    pub button: T,
+    pub conflicting: CatAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictResolution {
+    Swap,    // the conflicting action takes over the old binding
+    Replace, // the conflicting action is left unbound
+    Cancel,
+}
+
+#[derive(Event)]
+pub struct ConflictResolvedEvent(pub ConflictResolution);
+
+// unbinds the action's key, the settings row only shows the keyboard so the gamepad is left alone
+#[derive(Event)]
+pub struct ClearBindingEvent(pub CatAction);
+
+#[derive(Event)]
+pub struct RestoreDefaultControllsEvent;
+
+#[derive(Event)]
+pub struct CycleGamepadEvent;
+
+// player two's half of the keyboard in split keyboard co-op
+#[derive(Resource)]
+pub struct SecondKeyboard(pub Controlls<KeyCode>);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InputDevice {
+    Keyboard,
+    SecondKeyboard,
+    Mouse,
+    Gamepad,
+}
+
+pub trait ControllDevice {
+    const DEVICE: InputDevice;
+}
+impl ControllDevice for KeyCode {
+    const DEVICE: InputDevice = InputDevice::Keyboard;
+}
+impl ControllDevice for MouseButton {
+    const DEVICE: InputDevice = InputDevice::Mouse;
+}
+impl ControllDevice for GamepadButton {
+    const DEVICE: InputDevice = InputDevice::Gamepad;
+}
+
+// the actions a cat's player is holding this frame, gathered from every device they own
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
+pub struct CatInput {
+    pressed: HashSet<CatAction>,
+    just_pressed: HashSet<CatAction>,
+    pub analogue: f32, // left stick x, zero inside the deadzone
+}
+impl CatInput {
+    pub fn pressed(&self, action: CatAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: CatAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn press(&mut self, action: CatAction, just_pressed: bool) {
+        self.pressed.insert(action);
+        if just_pressed {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    fn read<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: &Input<T>,
+        controller: &Controlls<T>,
+    ) {
+        for action in ACTION_LIST {
+            let Some(button) = controller.get(action) else {
+                continue;
+            };
+            if input.pressed(button) {
+                self.press(action, input.just_pressed(button));
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pressed.clear();
+        self.just_pressed.clear();
+        self.analogue = 0.0;
+    }
+}
 
 #[derive(Resource)]
 pub struct Controlls<T> {
@@ -21,9 +148,60 @@ pub struct Controlls<T> {
     pub toggle_weapon: Option<T>,
     pub place_block: Option<T>,
     pub pause: Option<T>,
+    pub restart: Option<T>,
+}
+
+impl<T: Copy + Eq> Controlls<T> {
+    pub fn get(&self, action: CatAction) -> Option<T> {
+        match action {
+            CatAction::Up => self.up,
+            CatAction::Left => self.left,
+            CatAction::Right => self.right,
+            CatAction::Jump => self.jump,
+            CatAction::Fire => self.fire,
+            CatAction::ToggleWeapon => self.toggle_weapon,
+            CatAction::PlaceBlock => self.place_block,
+            CatAction::Pause => self.pause,
+            CatAction::Restart => self.restart,
+        }
+    }
+
+    // returns the action already using `button`, ignoring `except`
+    pub fn bound_to(&self, button: T, except: CatAction) -> Option<CatAction> {
+        ACTION_LIST
+            .into_iter()
+            .filter(|&action| action != except)
+            .find(|&action| self.get(action) == Some(button))
+    }
+}
+
+impl Controlls<GamepadButton> {
+    // points every binding at `gamepad` while keeping the chosen button types
+    fn retarget(&mut self, gamepad: Gamepad) {
+        for action in ACTION_LIST {
+            if let Some(button) = self.get(action) {
+                let button = GamepadButton::new(gamepad, button.button_type);
+                self.set(action, Some(button));
+            }
+        }
+    }
 }
 
 impl<T> Controlls<T> {
+    fn set(&mut self, action: CatAction, button: Option<T>) {
+        match action {
+            CatAction::Up => self.up = button,
+            CatAction::Left => self.left = button,
+            CatAction::Right => self.right = button,
+            CatAction::Jump => self.jump = button,
+            CatAction::Fire => self.fire = button,
+            CatAction::ToggleWeapon => self.toggle_weapon = button,
+            CatAction::PlaceBlock => self.place_block = button,
+            CatAction::Pause => self.pause = button,
+            CatAction::Restart => self.restart = button,
+        }
+    }
+
     fn empty() -> Self {
         Self {
             up: None,
@@ -35,11 +213,12 @@ impl<T> Controlls<T> {
             toggle_weapon: None,
             place_block: None,
             pause: None,
+            restart: None,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CatAction {
     Up,
     Left,
@@ -50,9 +229,10 @@ pub enum CatAction {
     ToggleWeapon,
     PlaceBlock,
     Pause,
+    Restart,
 }
 
-pub const ACTION_LIST: [CatAction; 8] = [
+pub const ACTION_LIST: [CatAction; 9] = [
     CatAction::Up,
     CatAction::Left,
     CatAction::Right,
@@ -61,6 +241,7 @@ pub const ACTION_LIST: [CatAction; 8] = [
     CatAction::ToggleWeapon,
     CatAction::PlaceBlock,
     CatAction::Pause,
+    CatAction::Restart,
 ];
 
 pub struct ControllsPlugin;
@@ -69,37 +250,146 @@ impl Plugin for ControllsPlugin {
         app.insert_resource(Controlls::<KeyCode>::empty())
             .insert_resource(Controlls::<GamepadButton>::empty())
             .insert_resource(Controlls::<MouseButton>::empty())
+            .insert_resource(SecondKeyboard(Controlls::empty()))
             .insert_resource(CurrentGamepad(None))
-            .add_systems(Startup, (initialize_mouse_buttons, initialize_kbd_buttons))
+            .add_event::<ConflictResolvedEvent>()
+            .add_event::<ClearBindingEvent>()
+            .add_event::<RestoreDefaultControllsEvent>()
+            .add_event::<CycleGamepadEvent>()
+            .add_systems(
+                Startup,
+                (
+                    initialize_mouse_buttons,
+                    initialize_kbd_buttons,
+                    initialize_second_kbd_buttons,
+                    initialize_gamepad,
+                ),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    clear_cat_input,
+                    read_cat_input::<KeyCode>,
+                    read_cat_input::<MouseButton>,
+                    read_cat_input::<GamepadButton>,
+                    read_second_keyboard,
+                    read_analogue,
+                )
+                    .chain()
+                    .in_set(CatInputSet)
+                    .after(InputSystem),
+            )
+            .add_systems(OnExit(GameState::Settings), cancel_controll_change)
+            .add_systems(OnExit(SimulationState::Settings), cancel_controll_change)
             .add_systems(
                 Update,
                 (
-                    initialize_gamepad.run_if(resource_changed::<Gamepads>()),
+                    handle_gamepad_connection.run_if(on_event::<GamepadEvent>()),
+                    cycle_gamepad.run_if(on_event::<CycleGamepadEvent>()),
                     handle_controll_change.run_if(resource_exists::<ControllChange>()),
+                    resolve_conflict::<KeyCode>
+                        .run_if(resource_exists::<ControllConflict<KeyCode>>()),
+                    resolve_conflict::<GamepadButton>
+                        .run_if(resource_exists::<ControllConflict<GamepadButton>>()),
+                    clear_binding.run_if(on_event::<ClearBindingEvent>()),
+                    (initialize_kbd_buttons, initialize_gamepad)
+                        .run_if(on_event::<RestoreDefaultControllsEvent>()),
                 ),
             );
     }
 }
 
+// bindings are kept on the first gamepad until a real one connects and takes them over
 pub fn initialize_gamepad(
+    mut controller: ResMut<Controlls<GamepadButton>>,
+    current: Res<CurrentGamepad>,
+) {
+    let gamepad = current.0.unwrap_or(Gamepad::new(0));
+
+    controller.up = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadUp));
+    controller.left = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft));
+    controller.right = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadRight));
+    controller.jump = Some(GamepadButton::new(gamepad, GamepadButtonType::South));
+    controller.fire = Some(GamepadButton::new(
+        gamepad,
+        GamepadButtonType::RightTrigger2,
+    ));
+    controller.toggle_weapon = Some(GamepadButton::new(gamepad, GamepadButtonType::North));
+    controller.place_block = Some(GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger));
+    controller.pause = Some(GamepadButton::new(gamepad, GamepadButtonType::Start));
+    controller.restart = None;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_gamepad_connection(
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+    mut controller: ResMut<Controlls<GamepadButton>>,
+    mut current: ResMut<CurrentGamepad>,
+    mut next_sim_state: ResMut<NextState<SimulationState>>,
+    // kept from the connection, Gamepads has already forgotten a pad by the time it is gone
+    mut names: Local<HashMap<Gamepad, String>>,
+    sim_state: Res<State<SimulationState>>,
+    locale: Res<Locale>,
+) {
+    for event in gamepad_events.read() {
+        let GamepadEvent::Connection(connection) = event else {
+            continue;
+        };
+
+        match &connection.connection {
+            GamepadConnection::Connected(info) => {
+                toast_writer.send(ToastEvent(
+                    locale.format("gamepad-connected", &[&info.name]),
+                ));
+                names.insert(connection.gamepad, info.name.clone());
+
+                if current.0.is_none() {
+                    current.0 = Some(connection.gamepad);
+                    controller.retarget(connection.gamepad);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                let name = names
+                    .remove(&connection.gamepad)
+                    .unwrap_or_else(|| format!("Gamepad {}", connection.gamepad.id));
+                toast_writer.send(ToastEvent(locale.format("gamepad-disconnected", &[&name])));
+
+                if current.0 != Some(connection.gamepad) {
+                    continue;
+                }
+                current.0 = None;
+
+                if *sim_state.get() == SimulationState::Running {
+                    next_sim_state.set(SimulationState::Paused);
+                }
+            }
+        }
+    }
+}
+
+fn cycle_gamepad(
+    mut cycle_reader: EventReader<CycleGamepadEvent>,
     mut controller: ResMut<Controlls<GamepadButton>>,
     mut current: ResMut<CurrentGamepad>,
     gamepads: Res<Gamepads>,
 ) {
-    if let Some(gamepad) = gamepads.iter().next() {
-        current.0 = Some(gamepad); // required for axis controlls :/
+    for _ in cycle_reader.read() {
+        let mut connected: Vec<Gamepad> = gamepads.iter().collect();
+        connected.sort_by_key(|gamepad| gamepad.id);
 
-        controller.up = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadUp));
-        controller.left = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadLeft));
-        controller.right = Some(GamepadButton::new(gamepad, GamepadButtonType::DPadRight));
-        controller.jump = Some(GamepadButton::new(gamepad, GamepadButtonType::South));
-        controller.fire = Some(GamepadButton::new(
-            gamepad,
-            GamepadButtonType::RightTrigger2,
-        ));
-        controller.toggle_weapon = Some(GamepadButton::new(gamepad, GamepadButtonType::North));
-        controller.place_block = Some(GamepadButton::new(gamepad, GamepadButtonType::LeftTrigger));
-        controller.pause = Some(GamepadButton::new(gamepad, GamepadButtonType::Start));
+        let next = match current.0 {
+            Some(active) => connected
+                .iter()
+                .find(|gamepad| gamepad.id > active.id)
+                .or(connected.first()),
+            None => connected.first(),
+        };
+
+        if let Some(&gamepad) = next {
+            current.0 = Some(gamepad);
+            controller.retarget(gamepad);
+        }
     }
 }
 
@@ -118,42 +408,189 @@ fn initialize_kbd_buttons(mut controller: ResMut<Controlls<KeyCode>>) {
     controller.toggle_weapon = Some(KeyCode::F);
     controller.place_block = Some(KeyCode::ShiftLeft);
     controller.pause = Some(KeyCode::Escape);
+    controller.restart = Some(KeyCode::R);
+}
+
+fn initialize_second_kbd_buttons(mut controller: ResMut<SecondKeyboard>) {
+    let controller = &mut controller.0;
+    controller.up = Some(KeyCode::Up);
+    controller.left = Some(KeyCode::Left);
+    controller.right = Some(KeyCode::Right);
+
+    controller.fire = Some(KeyCode::ControlRight);
+    controller.toggle_weapon = Some(KeyCode::Period);
+    controller.place_block = Some(KeyCode::ShiftRight);
+}
+
+fn clear_cat_input(mut query: Query<&mut CatInput>) {
+    for mut cat_input in &mut query {
+        cat_input.clear();
+    }
+}
+
+fn read_cat_input<T: ControllDevice + Copy + Eq + Hash + Send + Sync + 'static>(
+    mut query: Query<(&Player, &mut CatInput)>,
+    input: Res<Input<T>>,
+    controller: Res<Controlls<T>>,
+    coop_mode: Res<CoopMode>,
+) {
+    for (&player, mut cat_input) in &mut query {
+        if coop_mode.owner(T::DEVICE) == Some(player) {
+            cat_input.read(&input, &controller);
+        }
+    }
+}
+
+fn read_second_keyboard(
+    mut query: Query<(&Player, &mut CatInput)>,
+    input: Res<Input<KeyCode>>,
+    controller: Res<SecondKeyboard>,
+    coop_mode: Res<CoopMode>,
+) {
+    for (&player, mut cat_input) in &mut query {
+        if coop_mode.owner(InputDevice::SecondKeyboard) == Some(player) {
+            cat_input.read(&input, &controller.0);
+        }
+    }
+}
+
+fn read_analogue(
+    mut query: Query<(&Player, &mut CatInput)>,
+    current: Res<CurrentGamepad>,
+    axes: Res<Axis<GamepadAxis>>,
+    coop_mode: Res<CoopMode>,
+) {
+    let Some(id) = current.0 else {
+        return;
+    };
+
+    let leftaxis_x = GamepadAxis::new(id, GamepadAxisType::LeftStickX);
+    let leftaxis_y = GamepadAxis::new(id, GamepadAxisType::LeftStickY);
+
+    let (Some(x), Some(y)) = (axes.get(leftaxis_x), axes.get(leftaxis_y)) else {
+        return;
+    };
+
+    let leftaxis = Vec2::new(x, y);
+    if leftaxis.length() <= ANALOGUE_DEADZONE || leftaxis.x.abs() <= ANALOGUE_DEADZONE {
+        return;
+    }
+
+    for (&player, mut cat_input) in &mut query {
+        if coop_mode.owner(InputDevice::Gamepad) == Some(player) {
+            cat_input.analogue = leftaxis.length() * leftaxis.x.signum();
+        }
+    }
 }
 
-fn handle_controll_change(
+pub fn handle_controll_change(
     mut commands: Commands,
     mut kbd_events: EventReader<KeyboardInput>,
     mut gamepad_events: EventReader<GamepadButtonInput>,
     mut kbd_controller: ResMut<Controlls<KeyCode>>, // will needa change this in the future
     mut gamepad_controller: ResMut<Controlls<GamepadButton>>, // will needa change this in the future
-    controllchange: Res<ControllChange>,
+    mut controllchange: ResMut<ControllChange>,
+    time: Res<Time>,
 ) {
-    if let Some(key_event) = kbd_events.read().next() {
-        update_button(&mut kbd_controller, controllchange.0, key_event.key_code);
+    // the key that opened the prompt (a menu confirm) must not become the new binding
+    if controllchange.is_added() {
+        kbd_events.clear();
+        gamepad_events.clear();
+        return;
+    }
+
+    if controllchange.1.tick(time.delta()).finished() {
+        commands.remove_resource::<ControllChange>();
+        return;
+    }
+
+    let action = controllchange.0;
+
+    let key_press = kbd_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .find_map(|event| event.key_code);
+
+    let button_press = gamepad_events
+        .read()
+        .find(|event| event.state == ButtonState::Pressed)
+        .map(|event| event.button);
+
+    if let Some(key) = key_press {
+        if key != CANCEL_KEY {
+            bind_or_conflict(&mut commands, &mut kbd_controller, action, key);
+        }
         commands.remove_resource::<ControllChange>();
-    } else if let Some(button_event) = gamepad_events.read().next() {
-        update_button(
-            &mut gamepad_controller,
-            controllchange.0,
-            Some(button_event.button),
-        );
+    } else if let Some(button) = button_press {
+        if button.button_type != CANCEL_BUTTON {
+            bind_or_conflict(&mut commands, &mut gamepad_controller, action, button);
+        }
         commands.remove_resource::<ControllChange>();
     }
 }
 
+fn bind_or_conflict<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    commands: &mut Commands,
+    controller: &mut ResMut<Controlls<T>>,
+    action: CatAction,
+    button: T,
+) {
+    if let Some(conflicting) = controller.bound_to(button, action) {
+        commands.insert_resource(ControllConflict {
+            action,
+            button,
+            conflicting,
+        });
+    } else {
+        update_button(controller, action, Some(button));
+    }
+}
+
+fn resolve_conflict<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    mut commands: Commands,
+    mut resolved_reader: EventReader<ConflictResolvedEvent>,
+    mut controller: ResMut<Controlls<T>>,
+    conflict: Res<ControllConflict<T>>,
+) {
+    let Some(ConflictResolvedEvent(resolution)) = resolved_reader.read().last() else {
+        return;
+    };
+
+    match resolution {
+        ConflictResolution::Swap => {
+            let old_button = controller.get(conflict.action);
+            update_button(&mut controller, conflict.conflicting, old_button);
+            update_button(&mut controller, conflict.action, Some(conflict.button));
+        }
+        ConflictResolution::Replace => {
+            update_button(&mut controller, conflict.conflicting, None);
+            update_button(&mut controller, conflict.action, Some(conflict.button));
+        }
+        ConflictResolution::Cancel => (),
+    }
+
+    commands.remove_resource::<ControllConflict<T>>();
+}
+
+fn clear_binding(
+    mut clear_reader: EventReader<ClearBindingEvent>,
+    mut kbd_controller: ResMut<Controlls<KeyCode>>,
+) {
+    for ClearBindingEvent(action) in clear_reader.read() {
+        update_button(&mut kbd_controller, *action, None);
+    }
+}
+
+fn cancel_controll_change(mut commands: Commands) {
+    commands.remove_resource::<ControllChange>();
+    commands.remove_resource::<ControllConflict<KeyCode>>();
+    commands.remove_resource::<ControllConflict<GamepadButton>>();
+}
+
 pub fn update_button<T: Copy + Eq + Hash + Send + Sync + 'static>(
     controller: &mut ResMut<Controlls<T>>,
     action: CatAction,
     button: Option<T>,
 ) {
-    match action {
-        CatAction::Up => controller.up = button,
-        CatAction::Left => controller.left = button,
-        CatAction::Right => controller.right = button,
-        CatAction::Jump => controller.jump = button,
-        CatAction::Fire => controller.fire = button,
-        CatAction::ToggleWeapon => controller.toggle_weapon = button,
-        CatAction::PlaceBlock => controller.place_block = button,
-        CatAction::Pause => controller.pause = button,
-    }
+    controller.set(action, button);
 }
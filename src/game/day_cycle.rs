@@ -0,0 +1,94 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use super::{tuning::Tuning, Background, GameState, SimulationState};
+use crate::{accessibility::Theme, viewport::ArenaScreen};
+
+const NIGHT_SKY: Color = Color::rgb(0.12, 0.12, 0.28);
+const NIGHT_TINT: Color = Color::rgb(0.45, 0.5, 0.8); // multiplies everything else in the arena
+const NIGHT_DAYLIGHT: f32 = 0.25; // below this much daylight the night bugs come out
+
+// how far the run is through its days, every run starts at noon
+#[derive(Resource)]
+pub struct DayCycle {
+    elapsed: f32,
+    pub daylight: f32, // 1 at noon, 0 at midnight
+}
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            daylight: 1.0,
+        }
+    }
+}
+impl DayCycle {
+    pub fn is_night(&self) -> bool {
+        self.daylight < NIGHT_DAYLIGHT
+    }
+}
+
+pub struct DayCyclePlugin;
+impl Plugin for DayCyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DayCycle>()
+            .add_systems(OnEnter(GameState::Game), reset_day_cycle)
+            .add_systems(OnExit(GameState::Game), clear_arena_tint)
+            .add_systems(
+                Update,
+                advance_day
+                    .run_if(in_state(GameState::Game))
+                    .run_if(in_state(SimulationState::Running)),
+            )
+            .add_systems(
+                Update,
+                tint_arena
+                    .after(advance_day)
+                    .run_if(in_state(GameState::Game))
+                    .run_if(resource_changed::<DayCycle>().or_else(resource_changed::<Theme>())),
+            );
+    }
+}
+
+fn reset_day_cycle(mut day_cycle: ResMut<DayCycle>) {
+    *day_cycle = DayCycle::default();
+}
+
+pub fn advance_day(mut day_cycle: ResMut<DayCycle>, time: Res<Time>, tuning: Res<Tuning>) {
+    day_cycle.elapsed += time.delta_seconds();
+    day_cycle.daylight = 0.5 + 0.5 * (day_cycle.elapsed / tuning.day_length * TAU).cos();
+}
+
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    Color::rgba(
+        from.r() + (to.r() - from.r()) * t,
+        from.g() + (to.g() - from.g()) * t,
+        from.b() + (to.b() - from.b()) * t,
+        from.a() + (to.a() - from.a()) * t,
+    )
+}
+
+// the sky darkens on its own, everything drawn over it is dimmed through the upscaled arena
+fn tint_arena(
+    mut background_query: Query<&mut Sprite, (With<Background>, Without<ArenaScreen>)>,
+    mut screen_query: Query<&mut Sprite, With<ArenaScreen>>,
+    day_cycle: Res<DayCycle>,
+    theme: Res<Theme>,
+) {
+    let darkness = 1.0 - day_cycle.daylight;
+
+    if let Ok(mut background_sprite) = background_query.get_single_mut() {
+        background_sprite.color = mix(theme.background, NIGHT_SKY, darkness);
+    }
+    if let Ok(mut screen_sprite) = screen_query.get_single_mut() {
+        screen_sprite.color = mix(Color::WHITE, NIGHT_TINT, darkness);
+    }
+}
+
+fn clear_arena_tint(mut screen_query: Query<&mut Sprite, With<ArenaScreen>>) {
+    let Ok(mut screen_sprite) = screen_query.get_single_mut() else {
+        return;
+    };
+    screen_sprite.color = Color::WHITE;
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use super::{
+    bugs::{Bug, BUG_SIZE},
+    bullet::{Bullet, BULLET_SIZE},
+    cat::{get_min_max, Cat, CAT_SIZE},
+    clouds::Cloud,
+    flora::Flora,
+    ground::{Ground, TempGround, GROUND_WIDTH},
+    DifficultyMultiplier, GameState, SimulationState,
+};
+use crate::viewport::{ARENA_HEIGHT, ARENA_WIDTH};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const HITBOX_COLOR: Color = Color::LIME_GREEN;
+const BULLET_RANGE_COLOR: Color = Color::ORANGE_RED;
+const VELOCITY_COLOR: Color = Color::CYAN;
+const BOUNDS_COLOR: Color = Color::FUCHSIA;
+const VELOCITY_SCALE: f32 = 0.25; // velocity vectors are drawn a quarter second long
+
+// everything that gets a velocity vector
+type Moving = Or<(With<Cat>, With<Bug>, With<Bullet>)>;
+
+#[derive(Resource, Default)]
+struct DebugOverlay(bool);
+
+#[derive(Component)]
+struct DebugPanel;
+
+pub struct DebugPlugin;
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .init_resource::<DebugOverlay>()
+            .add_systems(Startup, spawn_debug_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    show_debug_panel
+                        .after(toggle_debug_overlay)
+                        .run_if(resource_changed::<DebugOverlay>()),
+                    (
+                        draw_hitboxes,
+                        draw_velocities,
+                        draw_confinement_bounds,
+                        update_debug_panel,
+                    )
+                        .after(toggle_debug_overlay)
+                        .run_if(overlay_enabled),
+                ),
+            );
+    }
+}
+
+fn overlay_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.0
+}
+
+fn toggle_debug_overlay(mut overlay: ResMut<DebugOverlay>, keys: Res<Input<KeyCode>>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn spawn_debug_panel(mut commands: Commands) {
+    let panel = TextBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font_size: 12.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(5.0),
+            bottom: Val::Px(5.0),
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+        z_index: ZIndex::Global(3),
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands.spawn((panel, DebugPanel));
+}
+
+fn show_debug_panel(
+    mut panel_query: Query<&mut Visibility, With<DebugPanel>>,
+    overlay: Res<DebugOverlay>,
+) {
+    let Ok(mut visibility) = panel_query.get_single_mut() else {
+        return;
+    };
+    *visibility = if overlay.0 {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn draw_hitboxes(
+    mut gizmos: Gizmos,
+    cat_query: Query<&Transform, With<Cat>>,
+    bug_query: Query<&Transform, With<Bug>>,
+    bullet_query: Query<&Transform, With<Bullet>>,
+    ground_query: Query<&Transform, With<Ground>>,
+) {
+    for transform in &cat_query {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            Vec2::splat(CAT_SIZE),
+            HITBOX_COLOR,
+        );
+    }
+    for transform in &bug_query {
+        let position = transform.translation.truncate();
+        gizmos.rect_2d(position, 0.0, Vec2::splat(BUG_SIZE), HITBOX_COLOR);
+        // eat_bullet_bug hits anything whose centre is within this circle
+        gizmos.circle_2d(position, BUG_SIZE, BULLET_RANGE_COLOR);
+    }
+    for transform in &bullet_query {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            Vec2::splat(BULLET_SIZE),
+            HITBOX_COLOR,
+        );
+    }
+    for transform in &ground_query {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.0,
+            Vec2::splat(GROUND_WIDTH),
+            HITBOX_COLOR,
+        );
+    }
+}
+
+// velocities are worked out from how far things moved since the last frame,
+// so every moving thing is covered however it stores its speed
+fn draw_velocities(
+    mut gizmos: Gizmos,
+    mut last_positions: Local<HashMap<Entity, Vec2>>,
+    moving_query: Query<(Entity, &Transform), Moving>,
+    time: Res<Time>,
+) {
+    if time.delta_seconds() == 0.0 {
+        return;
+    }
+
+    let mut positions = HashMap::new();
+    for (entity, transform) in &moving_query {
+        let position = transform.translation.truncate();
+        if let Some(&last) = last_positions.get(&entity) {
+            let velocity = (position - last) / time.delta_seconds();
+            gizmos.ray_2d(position, velocity * VELOCITY_SCALE, VELOCITY_COLOR);
+        }
+        positions.insert(entity, position);
+    }
+    *last_positions = positions;
+}
+
+// the area confine_cat keeps the centre of each cat in
+fn draw_confinement_bounds(mut gizmos: Gizmos) {
+    let (y_min, y_max) = get_min_max(ARENA_HEIGHT);
+    let (x_min, x_max) = get_min_max(ARENA_WIDTH);
+    gizmos.rect_2d(
+        Vec2::new(x_min + x_max, y_min + y_max) / 2.0,
+        0.0,
+        Vec2::new(x_max - x_min, y_max - y_min),
+        BOUNDS_COLOR,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_debug_panel(
+    mut panel_query: Query<&mut Text, With<DebugPanel>>,
+    diagnostics: Res<DiagnosticsStore>,
+    entity_query: Query<Entity>,
+    cat_query: Query<(), With<Cat>>,
+    bug_query: Query<(), With<Bug>>,
+    bullet_query: Query<(), With<Bullet>>,
+    ground_query: Query<(), (With<Ground>, Without<TempGround>)>,
+    temp_ground_query: Query<(), With<TempGround>>,
+    flora_query: Query<(), With<Flora>>,
+    cloud_query: Query<(), With<Cloud>>,
+    game_state: Res<State<GameState>>,
+    simulation_state: Res<State<SimulationState>>,
+    difficulty: Res<DifficultyMultiplier>,
+) {
+    let Ok(mut text) = panel_query.get_single_mut() else {
+        return;
+    };
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+
+    text.sections[0].value = format!(
+        "fps: {:.0}\n\
+         entities: {}\n\
+         cats: {}  bugs: {}  bullets: {}\n\
+         ground: {}  temp ground: {}\n\
+         flora: {}  clouds: {}\n\
+         state: {:?} / {:?}\n\
+         difficulty: {:.2}x",
+        fps,
+        entity_query.iter().count(),
+        cat_query.iter().count(),
+        bug_query.iter().count(),
+        bullet_query.iter().count(),
+        ground_query.iter().count(),
+        temp_ground_query.iter().count(),
+        flora_query.iter().count(),
+        cloud_query.iter().count(),
+        game_state.get(),
+        simulation_state.get(),
+        difficulty.0,
+    );
+}
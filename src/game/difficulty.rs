@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Custom, // uses whatever the sliders were set to
+}
+impl DifficultyPreset {
+    pub fn next(&self) -> Self {
+        match self {
+            DifficultyPreset::Easy => DifficultyPreset::Normal,
+            DifficultyPreset::Normal => DifficultyPreset::Hard,
+            DifficultyPreset::Hard => DifficultyPreset::Custom,
+            DifficultyPreset::Custom => DifficultyPreset::Easy,
+        }
+    }
+}
+
+// the numbers a run is tuned with, filled in from the preset before the run starts
+#[derive(Resource, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyConfig {
+    pub initial_hearts: u8,
+    pub difficulty_step: f32, // added to the multiplier every 50 points
+    pub difficulty_upper_limit: f32,
+    pub bug_spawn_rate: f32, // seconds between bugs
+    pub bug_speed: f32,
+}
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        Self::from_preset(DifficultyPreset::Normal)
+    }
+}
+// what the sliders were set to, kept apart so picking another preset does not lose them
+#[derive(Resource, Clone, Copy, Default)]
+pub struct CustomDifficulty(pub DifficultyConfig);
+
+impl DifficultyConfig {
+    fn from_preset(preset: DifficultyPreset) -> Self {
+        match preset {
+            DifficultyPreset::Easy => Self {
+                initial_hearts: 8,
+                difficulty_step: 0.1,
+                difficulty_upper_limit: 3.0,
+                bug_spawn_rate: 1.1,
+                bug_speed: 16.0,
+            },
+            DifficultyPreset::Normal | DifficultyPreset::Custom => Self {
+                initial_hearts: 5,
+                difficulty_step: 0.15,
+                difficulty_upper_limit: 4.0,
+                bug_spawn_rate: 0.84,
+                bug_speed: 20.0,
+            },
+            DifficultyPreset::Hard => Self {
+                initial_hearts: 3,
+                difficulty_step: 0.2,
+                difficulty_upper_limit: 5.0,
+                bug_spawn_rate: 0.6,
+                bug_speed: 25.0,
+            },
+        }
+    }
+
+    pub fn get(&self, param: DifficultyParam) -> f32 {
+        match param {
+            DifficultyParam::InitialHearts => self.initial_hearts as f32,
+            DifficultyParam::DifficultyStep => self.difficulty_step,
+            DifficultyParam::DifficultyUpperLimit => self.difficulty_upper_limit,
+            DifficultyParam::BugSpawnRate => self.bug_spawn_rate,
+            DifficultyParam::BugSpeed => self.bug_speed,
+        }
+    }
+
+    // snaps the value to the parameter's range and step
+    pub fn set(&mut self, param: DifficultyParam, value: f32) {
+        let (min, max, step) = param.range();
+        let value = (((value - min) / step).round() * step + min).clamp(min, max);
+
+        match param {
+            DifficultyParam::InitialHearts => self.initial_hearts = value as u8,
+            DifficultyParam::DifficultyStep => self.difficulty_step = value,
+            DifficultyParam::DifficultyUpperLimit => self.difficulty_upper_limit = value,
+            DifficultyParam::BugSpawnRate => self.bug_spawn_rate = value,
+            DifficultyParam::BugSpeed => self.bug_speed = value,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifficultyParam {
+    InitialHearts,
+    DifficultyStep,
+    DifficultyUpperLimit,
+    BugSpawnRate,
+    BugSpeed,
+}
+impl DifficultyParam {
+    // min, max and step of the custom difficulty sliders
+    pub fn range(&self) -> (f32, f32, f32) {
+        match self {
+            DifficultyParam::InitialHearts => (1.0, 10.0, 1.0),
+            DifficultyParam::DifficultyStep => (0.05, 0.5, 0.05),
+            DifficultyParam::DifficultyUpperLimit => (1.5, 6.0, 0.5),
+            DifficultyParam::BugSpawnRate => (0.3, 2.0, 0.1),
+            DifficultyParam::BugSpeed => (10.0, 40.0, 2.0),
+        }
+    }
+}
+
+pub const DIFFICULTY_PARAMS: [DifficultyParam; 5] = [
+    DifficultyParam::InitialHearts,
+    DifficultyParam::DifficultyStep,
+    DifficultyParam::DifficultyUpperLimit,
+    DifficultyParam::BugSpawnRate,
+    DifficultyParam::BugSpeed,
+];
+
+pub struct DifficultyPlugin;
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DifficultyPreset>()
+            .init_resource::<DifficultyConfig>()
+            .init_resource::<CustomDifficulty>()
+            .add_systems(
+                PreUpdate,
+                apply_difficulty_preset.run_if(
+                    resource_changed::<DifficultyPreset>()
+                        .or_else(resource_changed::<CustomDifficulty>()),
+                ),
+            );
+    }
+}
+
+fn apply_difficulty_preset(
+    mut config: ResMut<DifficultyConfig>,
+    preset: Res<DifficultyPreset>,
+    custom: Res<CustomDifficulty>,
+) {
+    *config = match *preset {
+        DifficultyPreset::Custom => custom.0,
+        preset => DifficultyConfig::from_preset(preset),
+    };
+}
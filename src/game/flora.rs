@@ -1,13 +1,12 @@
 use bevy::prelude::*;
 use rand::Rng;
 
-use super::ground::spawn_new_ground;
+use crate::loading::GameAssets;
 
-pub const FLORA_SPAWN_RATE: f32 = 0.12;
 const FLORA_SIZE: f32 = 16.0;
 
 #[derive(Component)]
-struct Flora;
+pub struct Flora;
 
 #[derive(Event)]
 pub struct FloraSpawnEvent(pub Entity);
@@ -15,26 +14,21 @@ pub struct FloraSpawnEvent(pub Entity);
 pub struct FloraPlugin;
 impl Plugin for FloraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<FloraSpawnEvent>().add_systems(
-            Update,
-            spawn_flora
-                .after(spawn_new_ground)
-                .run_if(on_event::<FloraSpawnEvent>()),
-        );
+        app.add_event::<FloraSpawnEvent>()
+            .add_systems(Update, spawn_flora.run_if(on_event::<FloraSpawnEvent>()));
     }
 }
 
 fn spawn_flora(
     mut commands: Commands,
     mut event_reader: EventReader<FloraSpawnEvent>,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
 ) {
     let mut rng = rand::thread_rng();
-    let mut random_sprite = || format!("sprites/flora/flora_{}.png", rng.gen_range(1..=6));
 
     for FloraSpawnEvent(entity) in event_reader.read() {
         let mut flora_sprite = SpriteBundle {
-            texture: asset_server.load(random_sprite()),
+            texture: game_assets.flora[rng.gen_range(0..game_assets.flora.len())].clone(),
             ..default()
         };
 
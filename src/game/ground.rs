@@ -1,19 +1,24 @@
-use bevy::{
-    prelude::*,
-    window::{PrimaryWindow, WindowResized},
-};
+use bevy::prelude::*;
 
 use super::{
     cat::CAT_SIZE,
-    flora::{FloraSpawnEvent, FLORA_SPAWN_RATE},
-    GameState, SCALE_FACTOR,
+    flora::FloraSpawnEvent,
+    particles::{spawn_emitter, BLOCK_DEBRIS},
+    tuning::Tuning,
+    GameState,
+};
+use crate::{
+    loading::GameAssets,
+    viewport::{ARENA_HEIGHT, ARENA_WIDTH},
 };
 use rand::random;
 
+#[cfg(debug_assertions)]
+use crate::console::{AddConsoleCommand, ConsoleCommandEvent, ConsoleReply};
+
 pub const GROUND_WIDTH: f32 = 16.0;
 pub const GROUND_HEIGHT: f32 = GROUND_WIDTH / 2.0;
 const GROUND_SPACING: f32 = 1.0;
-const MAX_TEMP_GROUND: usize = 4;
 
 #[derive(Component)]
 pub struct Ground;
@@ -32,48 +37,63 @@ impl Plugin for GroundPlugin {
             .add_systems(OnExit(GameState::Game), despawn_all_ground)
             .add_systems(
                 Update,
-                (despawn_old_ground, spawn_new_ground)
-                    .chain()
-                    .run_if(in_state(GameState::Game))
-                    .run_if(on_event::<WindowResized>()),
-            )
+                (spawn_beneath_cat, despawn_temp_ground).run_if(on_event::<GroundBuildEvent>()),
+            );
+
+        #[cfg(debug_assertions)]
+        app.add_console_command("spawn block", "spawn block <x> <y>", &[])
+            .add_console_command("clear blocks", "clear blocks", &[])
             .add_systems(
                 Update,
-                (spawn_beneath_cat, despawn_temp_ground).run_if(on_event::<GroundBuildEvent>()),
+                run_ground_commands.run_if(on_event::<ConsoleCommandEvent>()),
             );
     }
 }
 
+// spawn block places a block as if a cat stood at the point
+#[cfg(debug_assertions)]
+fn run_ground_commands(
+    mut commands: Commands,
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    mut reply_writer: EventWriter<ConsoleReply>,
+    mut ground_build_writer: EventWriter<GroundBuildEvent>,
+    temp_ground_query: Query<Entity, With<TempGround>>,
+) {
+    for command in command_reader.read() {
+        match command.name {
+            "spawn block" => {
+                let (Some(x), Some(y)) = (command.arg::<f32>(0), command.arg::<f32>(1)) else {
+                    reply_writer.send(ConsoleReply(format!("usage: {}", command.usage)));
+                    continue;
+                };
+                ground_build_writer.send(GroundBuildEvent(Vec3::new(x, y, 0.0)));
+            }
+            "clear blocks" => {
+                for entity in &temp_ground_query {
+                    commands.entity(entity).despawn();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
 fn spawn_ground(
     mut commands: Commands,
     mut event_writer: EventWriter<FloraSpawnEvent>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    tuning: Res<Tuning>,
 ) {
-    let Ok(window) = window_query.get_single() else {
-        warn!("No window Available");
-        return;
-    };
-
-    let raw_ground_count = (window.width() / SCALE_FACTOR) / GROUND_WIDTH;
-    let ground_count = raw_ground_count.floor();
-    let space = raw_ground_count - ground_count;
+    let ground_count = (ARENA_WIDTH / GROUND_WIDTH).floor();
+    let space = ARENA_WIDTH - ground_count * GROUND_WIDTH;
 
-    let initial_x_pos = -((window.width() / 2.0) / SCALE_FACTOR)
-        + GROUND_WIDTH / 2.0
-        + (space * SCALE_FACTOR) * 2.0;
-    let y_pos = -(window.height() / 2.0) / SCALE_FACTOR;
-
-    let random_sprite = || {
-        format!(
-            "sprites/ground/ground_{}.png",
-            if random::<bool>() { 1 } else { 2 }
-        )
-    };
+    // any leftover space is split between both ends
+    let initial_x_pos = -(ARENA_WIDTH / 2.0) + GROUND_WIDTH / 2.0 + space / 2.0;
+    let y_pos = -ARENA_HEIGHT / 2.0;
 
     for i in 0..ground_count as usize {
         let mut ground_sprite = SpriteBundle {
-            texture: asset_server.load(random_sprite()),
+            texture: game_assets.ground[random::<usize>() % game_assets.ground.len()].clone(),
             ..default()
         };
 
@@ -83,73 +103,22 @@ fn spawn_ground(
 
         let ground_entity = commands.spawn((ground_sprite, Ground)).id();
 
-        if random::<f32>() < FLORA_SPAWN_RATE {
+        if random::<f32>() < tuning.flora_spawn_rate {
             event_writer.send(FloraSpawnEvent(ground_entity));
         }
     }
 }
 
-pub(crate) fn spawn_new_ground(
-    mut commands: Commands,
-    mut window_resized_reader: EventReader<WindowResized>,
-    mut event_writer: EventWriter<FloraSpawnEvent>,
-    asset_server: Res<AssetServer>,
-) {
-    for window in window_resized_reader.read() {
-        let raw_ground_count = (window.width / SCALE_FACTOR) / GROUND_WIDTH;
-        let ground_count = raw_ground_count.floor();
-        let space = raw_ground_count - ground_count;
-
-        let initial_x_pos = -((window.width / 2.0) / SCALE_FACTOR)
-            + GROUND_WIDTH / 2.0
-            + (space * SCALE_FACTOR) * 2.0;
-        let y_pos = -(window.height / 2.0) / SCALE_FACTOR;
-
-        let random_sprite = || {
-            format!(
-                "sprites/ground/ground_{}.png",
-                if random::<bool>() { 1 } else { 2 }
-            )
-        };
-
-        for i in 0..ground_count as usize {
-            let mut ground_sprite = SpriteBundle {
-                texture: asset_server.load(random_sprite()),
-                ..default()
-            };
-
-            ground_sprite.transform.translation.y = y_pos;
-            ground_sprite.transform.translation.x =
-                initial_x_pos + (i as f32 * GROUND_WIDTH * GROUND_SPACING);
-
-            let ground_entity = commands.spawn((ground_sprite, Ground)).id();
-
-            if random::<f32>() < FLORA_SPAWN_RATE {
-                event_writer.send(FloraSpawnEvent(ground_entity));
-            }
-        }
-    }
-}
-
-fn despawn_old_ground(
-    mut commands: Commands,
-    ground_query: Query<Entity, (With<Ground>, Without<TempGround>)>,
-) {
-    for entity in &ground_query {
-        commands.entity(entity).despawn_recursive()
-    }
-}
-
 fn spawn_beneath_cat(
     mut commands: Commands,
     mut ground_build_reader: EventReader<GroundBuildEvent>,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
 ) {
     for event in ground_build_reader.read() {
         let cat_transform = event.0;
 
         let mut ground_sprite = SpriteBundle {
-            texture: asset_server.load("sprites/ground/temp_ground_new.png"),
+            texture: game_assets.temp_ground.clone(),
             ..default()
         };
 
@@ -161,20 +130,30 @@ fn spawn_beneath_cat(
     }
 }
 
-fn despawn_temp_ground(mut commands: Commands, query: Query<Entity, With<TempGround>>) {
-    let mut vec: Vec<Entity> = query.iter().collect();
-    if vec.len() < MAX_TEMP_GROUND {
+fn despawn_temp_ground(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), With<TempGround>>,
+    tuning: Res<Tuning>,
+) {
+    let mut vec: Vec<(Entity, &Transform)> = query.iter().collect();
+    if vec.len() < tuning.max_temp_ground {
         return;
     }
 
-    let removable = vec.len() - MAX_TEMP_GROUND;
-    vec.sort();
+    let removable = vec.len() - tuning.max_temp_ground;
+    vec.sort_by_key(|&(entity, _)| entity);
 
-    for (i, &entity) in vec.iter().enumerate() {
+    for (i, &(entity, transform)) in vec.iter().enumerate() {
         if i > removable {
             break;
         }
         commands.entity(entity).despawn();
+        spawn_emitter(
+            &mut commands,
+            BLOCK_DEBRIS,
+            transform.translation.truncate(),
+            Vec2::Y,
+        );
     }
 }
 
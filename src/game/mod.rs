@@ -1,26 +1,43 @@
-use std::hash::Hash;
-
-use crate::{GameState, SimulationState, SCALE_FACTOR};
-use bevy::{
-    prelude::*,
-    window::{PrimaryWindow, WindowResized},
+use crate::{
+    accessibility::Theme,
+    viewport::{ARENA_HEIGHT, ARENA_WIDTH},
+    GameState, SimulationState,
 };
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use self::controlls::{CatAction, CatInput, InputDevice};
+use self::difficulty::DifficultyConfig;
+use self::modes::GameMode;
 
-use self::controlls::Controlls;
+#[cfg(debug_assertions)]
+use self::camera_effects::TimeScale;
+#[cfg(debug_assertions)]
+use crate::console::{AddConsoleCommand, ConsoleCommandEvent, ConsoleReply};
 
+pub mod achievements;
+pub mod animation;
 mod bugs;
 mod bullet;
+mod camera_effects;
 mod cat;
 mod clouds;
 pub mod controlls;
+mod day_cycle;
+#[cfg(feature = "debug_overlay")]
+mod debug;
+pub mod difficulty;
 mod flora;
 mod ground;
-
-pub const INITIAL_HEART_COUNT: u8 = 5;
-const GRAVITY: f32 = 200.8;
-const FRICTION: f32 = 0.8;
-const DIFFICULTY_STEP: f32 = 0.15;
-const DIFFICULTY_UPPER_LIMIT: f32 = 4.0;
+pub mod modes;
+mod parallax;
+pub mod particles;
+pub mod replay;
+pub mod rng;
+pub mod stats;
+pub mod touch;
+pub mod tuning;
+pub mod weather;
 
 #[derive(Component)]
 struct Background;
@@ -34,9 +51,68 @@ pub struct Heart(pub u8);
 #[derive(Resource)]
 pub struct DifficultyMultiplier(pub f32);
 
+// score earned by each player's bullets, the shared Score is their sum
+#[derive(Resource)]
+pub struct PlayerScores(pub [u32; 2]);
+
+// starts a fresh run from the pause menu, the game over menu or the restart action
+#[derive(Event)]
+pub struct RestartEvent;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Player {
+    One,
+    Two,
+}
+impl Player {
+    pub fn index(&self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoopMode {
+    #[default]
+    Solo,
+    SplitKeyboard,      // player two uses the arrow keys side
+    KeyboardAndGamepad, // player two uses the gamepad
+}
+impl CoopMode {
+    pub fn players(&self) -> &'static [Player] {
+        match self {
+            CoopMode::Solo => &[Player::One],
+            _ => &[Player::One, Player::Two],
+        }
+    }
+
+    pub fn owner(&self, device: InputDevice) -> Option<Player> {
+        match (self, device) {
+            (_, InputDevice::Keyboard | InputDevice::Mouse) => Some(Player::One),
+            (CoopMode::SplitKeyboard, InputDevice::SecondKeyboard) => Some(Player::Two),
+            (CoopMode::KeyboardAndGamepad, InputDevice::Gamepad) => Some(Player::Two),
+            (_, InputDevice::Gamepad) => Some(Player::One),
+            (_, InputDevice::SecondKeyboard) => None,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CoopMode::Solo => CoopMode::SplitKeyboard,
+            CoopMode::SplitKeyboard => CoopMode::KeyboardAndGamepad,
+            CoopMode::KeyboardAndGamepad => CoopMode::Solo,
+        }
+    }
+}
+
 pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        #[cfg(feature = "debug_overlay")]
+        app.add_plugins(debug::DebugPlugin);
+
         app.add_state::<SimulationState>()
             .add_plugins((
                 bugs::BugPlugin,
@@ -46,13 +122,33 @@ impl Plugin for GamePlugin {
                 flora::FloraPlugin,
                 ground::GroundPlugin,
                 controlls::ControllsPlugin,
+                touch::TouchPlugin,
+                stats::StatsPlugin,
+                achievements::AchievementsPlugin,
+                modes::ModesPlugin,
+                difficulty::DifficultyPlugin,
+                tuning::TuningPlugin,
+                // bevy takes at most 15 plugins per tuple
+                (
+                    rng::RngPlugin,
+                    replay::ReplayPlugin,
+                    particles::ParticlesPlugin,
+                    camera_effects::CameraEffectsPlugin,
+                    parallax::ParallaxPlugin,
+                    day_cycle::DayCyclePlugin,
+                    weather::WeatherPlugin,
+                    animation::AnimationPlugin,
+                ),
             ))
             .insert_resource(Score(0))
-            .insert_resource(Heart(INITIAL_HEART_COUNT))
+            .insert_resource(Heart(0))
             .insert_resource(DifficultyMultiplier(1.0))
+            .insert_resource(PlayerScores([0; 2]))
+            .init_resource::<CoopMode>()
+            .add_event::<RestartEvent>()
             .add_systems(
                 OnEnter(GameState::Game),
-                (spawn_background, start_simulation),
+                (reset_stats, spawn_background, start_simulation),
             )
             .add_systems(
                 OnExit(GameState::Game),
@@ -61,34 +157,92 @@ impl Plugin for GamePlugin {
             .add_systems(
                 Update,
                 (
-                    toggle_simulation::<KeyCode>,
-                    toggle_simulation::<GamepadButton>,
-                    resize_bacground,
+                    toggle_simulation,
+                    // the restart binding may be getting changed in the settings overlay
+                    restart_on_input.run_if(not(in_state(SimulationState::Settings))),
                     game_over.run_if(resource_changed::<Heart>()),
                     step_difficulty.run_if(resource_changed::<Score>()),
                 )
                     .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(Update, restart_game.run_if(on_event::<RestartEvent>()))
+            .add_systems(OnEnter(GameState::Restarting), finish_restart);
+
+        #[cfg(debug_assertions)]
+        app.add_console_command("set score", "set score <points>", &[])
+            .add_console_command("set hearts", "set hearts <hearts>", &[])
+            .add_console_command(
+                "timescale",
+                "timescale <speed>",
+                &[&["0.25", "0.5", "1", "2"]],
+            )
+            .add_console_command(
+                "state",
+                "state <menu|modes|game|gameover|settings|achievements>",
+                &[&[
+                    "menu",
+                    "modes",
+                    "game",
+                    "gameover",
+                    "settings",
+                    "achievements",
+                ]],
+            )
+            .add_systems(
+                Update,
+                run_game_commands.run_if(on_event::<ConsoleCommandEvent>()),
             );
     }
 }
 
+#[cfg(debug_assertions)]
+fn run_game_commands(
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    mut reply_writer: EventWriter<ConsoleReply>,
+    mut score: ResMut<Score>,
+    mut hearts: ResMut<Heart>,
+    mut time_scale: ResMut<TimeScale>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for command in command_reader.read() {
+        let handled = match command.name {
+            "set score" => command.arg(0).map(|points| score.0 = points),
+            "set hearts" => command.arg(0).map(|count| hearts.0 = count),
+            "timescale" => command
+                .arg::<f32>(0)
+                .filter(|speed| *speed >= 0.0)
+                .map(|speed| time_scale.0 = speed),
+            "state" => {
+                let state = match command.args.first().map(String::as_str) {
+                    Some("menu") => Some(GameState::MainMenu),
+                    Some("modes") => Some(GameState::ModeSelect),
+                    Some("game") => Some(GameState::Game),
+                    Some("gameover") => Some(GameState::GameOver),
+                    Some("settings") => Some(GameState::Settings),
+                    Some("achievements") => Some(GameState::Achievements),
+                    _ => None,
+                };
+                state.map(|state| next_state.set(state))
+            }
+            _ => continue,
+        };
+
+        if handled.is_none() {
+            reply_writer.send(ConsoleReply(format!("usage: {}", command.usage)));
+        }
+    }
+}
+
 enum EntityDirection {
     Left,
     Right,
 }
 
-fn spawn_background(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-
+fn spawn_background(mut commands: Commands, theme: Res<Theme>) {
     let mut my_background = SpriteBundle {
         sprite: Sprite {
-            color: Color::hex("#fcdfcd").unwrap(),
-            custom_size: Some(Vec2::new(
-                window.width() / SCALE_FACTOR,
-                window.height() / SCALE_FACTOR,
-            )),
+            color: theme.background,
+            custom_size: Some(Vec2::new(ARENA_WIDTH, ARENA_HEIGHT)),
             ..default()
         },
         ..default()
@@ -105,21 +259,6 @@ fn despawn_background(mut commands: Commands, query: Query<Entity, With<Backgrou
     commands.entity(entity).despawn();
 }
 
-fn resize_bacground(
-    mut background_query: Query<&mut Sprite, With<Background>>,
-    mut window_reized_reader: EventReader<WindowResized>,
-) {
-    let Ok(mut background_sprite) = background_query.get_single_mut() else {
-        return;
-    };
-    for window_resized in window_reized_reader.read() {
-        background_sprite.custom_size = Some(Vec2::new(
-            window_resized.width / SCALE_FACTOR,
-            window_resized.height / SCALE_FACTOR,
-        ))
-    }
-}
-
 fn start_simulation(mut next_state: ResMut<NextState<SimulationState>>) {
     next_state.set(SimulationState::Running)
 }
@@ -128,62 +267,75 @@ fn stop_simulation(mut next_state: ResMut<NextState<SimulationState>>) {
     next_state.set(SimulationState::InActive)
 }
 
-fn toggle_simulation<T: Copy + Eq + Hash + Send + Sync + 'static>(
-    input: Res<Input<T>>,
-    controller: Res<Controlls<T>>,
+fn toggle_simulation(
+    cat_input_query: Query<&CatInput>,
     current_state: Res<State<SimulationState>>,
     mut next_state: ResMut<NextState<SimulationState>>,
 ) {
-    let Some(keypress) = controller.pause else {
-        return;
-    };
-
-    if !input.just_pressed(keypress) {
+    if !cat_input_query
+        .iter()
+        .any(|cat_input| cat_input.just_pressed(CatAction::Pause))
+    {
         return;
     }
 
     match *current_state.get() {
         SimulationState::Running => next_state.set(SimulationState::Paused),
         SimulationState::Paused => next_state.set(SimulationState::Running),
-        SimulationState::InActive => (),
+        SimulationState::InActive | SimulationState::Settings => (),
     }
 }
 
-// fn handle_gamepad(
-//     mut commands: Commands,
-//     current_gamepad: Option<Res<Controller>>,
-//     mut gamepad_evr: EventReader<GamepadEvent>,
-// ) {
-//     for event in gamepad_evr.read() {
-//         if let GamepadEvent::Connection(connection) = event {
-//             if connection.connected() {
-//                 if current_gamepad.is_some() { return; }
-//
-//                 commands.insert_resource(Controller(dbg!(connection.gamepad.id)))
-//             } else if let Some(ref controller) = current_gamepad {
-//                 if controller.0 == connection.gamepad.id {
-//                     commands.remove_resource::<Controller>()
-//                 }
-//             }
-//         }
-//     }
-// }
+fn restart_on_input(
+    cat_input_query: Query<&CatInput>,
+    mut restart_writer: EventWriter<RestartEvent>,
+) {
+    if cat_input_query
+        .iter()
+        .any(|cat_input| cat_input.just_pressed(CatAction::Restart))
+    {
+        restart_writer.send(RestartEvent);
+    }
+}
+
+// entering the state we are already in is ignored by bevy, so a restart
+// mid game goes through Restarting to get a real exit and enter
+fn restart_game(game_state: Res<State<GameState>>, mut next_state: ResMut<NextState<GameState>>) {
+    if *game_state.get() == GameState::Game {
+        next_state.set(GameState::Restarting);
+    } else {
+        next_state.set(GameState::Game);
+    }
+}
+
+fn finish_restart(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Game);
+}
 
 pub fn reset_stats(
     mut score: ResMut<Score>,
+    mut player_scores: ResMut<PlayerScores>,
     mut hearts: ResMut<Heart>,
     mut diffculty: ResMut<DifficultyMultiplier>,
+    mode: Res<GameMode>,
+    config: Res<DifficultyConfig>,
 ) {
     score.0 = 0;
-    hearts.0 = INITIAL_HEART_COUNT;
-    diffculty.0 = 1.0;
+    player_scores.0 = [0; 2];
+    hearts.0 = mode.initial_hearts(&config);
+    diffculty.0 = mode.initial_difficulty(&config);
 }
 
-fn step_difficulty(mut diffculty: ResMut<DifficultyMultiplier>, score: Res<Score>) {
-    if diffculty.0 >= DIFFICULTY_UPPER_LIMIT {
+fn step_difficulty(
+    mut diffculty: ResMut<DifficultyMultiplier>,
+    score: Res<Score>,
+    config: Res<DifficultyConfig>,
+) {
+    if diffculty.0 >= config.difficulty_upper_limit {
         return;
     }
-    diffculty.0 = 1.0 + ((score.0 / 50) as f32 * DIFFICULTY_STEP);
+    diffculty.0 =
+        (1.0 + ((score.0 / 50) as f32 * config.difficulty_step)).min(config.difficulty_upper_limit);
 }
 
 fn game_over(
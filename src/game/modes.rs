@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{difficulty::DifficultyConfig, GameState, Score, SimulationState};
+use crate::persistence::{read_file, write_file};
+
+const HIGH_SCORES_PATH: &str = "highscores.ron";
+const HIGH_SCORE_TABLE_LENGTH: usize = 5;
+const TIME_ATTACK_DURATION: f32 = 120.0;
+const SURVIVAL_POINT_INTERVAL: f32 = 1.0;
+
+#[derive(
+    Resource, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    TimeAttack, // score as much as possible before the clock runs out
+    Pacifist,   // no gun, points for every second survived
+    Hardcore,   // one heart at full speed
+}
+impl GameMode {
+    pub fn has_gun(&self) -> bool {
+        *self != GameMode::Pacifist
+    }
+
+    // pacifist cats can only get rid of bugs by landing on them
+    pub fn can_stomp(&self) -> bool {
+        *self == GameMode::Pacifist
+    }
+
+    pub fn initial_hearts(&self, config: &DifficultyConfig) -> u8 {
+        match self {
+            GameMode::Hardcore => 1,
+            _ => config.initial_hearts,
+        }
+    }
+
+    pub fn initial_difficulty(&self, config: &DifficultyConfig) -> f32 {
+        match self {
+            GameMode::Hardcore => config.difficulty_upper_limit,
+            _ => 1.0,
+        }
+    }
+
+    fn time_limit(&self) -> Option<f32> {
+        match self {
+            GameMode::TimeAttack => Some(TIME_ATTACK_DURATION),
+            _ => None,
+        }
+    }
+}
+
+pub const GAME_MODES: [GameMode; 4] = [
+    GameMode::Endless,
+    GameMode::TimeAttack,
+    GameMode::Pacifist,
+    GameMode::Hardcore,
+];
+
+// counts down in time attack, counts the next survival point in pacifist
+#[derive(Resource)]
+pub struct ModeTimer(pub Timer);
+
+// the best scores of each mode, highest first
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct HighScores(BTreeMap<GameMode, Vec<u32>>);
+impl HighScores {
+    pub fn table(&self, mode: GameMode) -> &[u32] {
+        self.0.get(&mode).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn best(&self, mode: GameMode) -> Option<u32> {
+        self.table(mode).first().copied()
+    }
+
+    fn record(&mut self, mode: GameMode, score: u32) {
+        let table = self.0.entry(mode).or_default();
+        table.push(score);
+        table.sort_unstable_by(|a, b| b.cmp(a));
+        table.truncate(HIGH_SCORE_TABLE_LENGTH);
+    }
+}
+
+pub struct ModesPlugin;
+impl Plugin for ModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+            .init_resource::<HighScores>()
+            .insert_resource(ModeTimer(Timer::from_seconds(0.0, TimerMode::Once)))
+            .add_systems(Startup, load_high_scores)
+            .add_systems(OnEnter(GameState::Game), reset_mode_timer)
+            .add_systems(OnEnter(GameState::GameOver), record_high_score)
+            .add_systems(
+                Update,
+                (end_time_attack, score_survival_time)
+                    .run_if(in_state(GameState::Game))
+                    .run_if(in_state(SimulationState::Running)),
+            )
+            .add_systems(
+                Update,
+                save_high_scores.run_if(resource_changed::<HighScores>()),
+            );
+    }
+}
+
+fn reset_mode_timer(mut mode_timer: ResMut<ModeTimer>, mode: Res<GameMode>) {
+    mode_timer.0 = match mode.time_limit() {
+        Some(limit) => Timer::from_seconds(limit, TimerMode::Once),
+        None => Timer::from_seconds(SURVIVAL_POINT_INTERVAL, TimerMode::Repeating),
+    };
+}
+
+fn end_time_attack(
+    mut mode_timer: ResMut<ModeTimer>,
+    mut simulation_state: ResMut<NextState<SimulationState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mode: Res<GameMode>,
+    time: Res<Time>,
+) {
+    if mode.time_limit().is_none() {
+        return;
+    }
+
+    if mode_timer.0.tick(time.delta()).just_finished() {
+        simulation_state.set(SimulationState::InActive);
+        game_state.set(GameState::GameOver);
+    }
+}
+
+fn score_survival_time(
+    mut mode_timer: ResMut<ModeTimer>,
+    mut score: ResMut<Score>,
+    mode: Res<GameMode>,
+    time: Res<Time>,
+) {
+    if *mode != GameMode::Pacifist {
+        return;
+    }
+
+    if mode_timer.0.tick(time.delta()).just_finished() {
+        score.0 += 1;
+    }
+}
+
+pub fn record_high_score(
+    mut high_scores: ResMut<HighScores>,
+    mode: Res<GameMode>,
+    score: Res<Score>,
+) {
+    high_scores.record(*mode, score.0);
+}
+
+fn load_high_scores(mut high_scores: ResMut<HighScores>) {
+    let Some(contents) = read_file(HIGH_SCORES_PATH) else {
+        return;
+    };
+
+    match ron::from_str(&contents) {
+        Ok(loaded) => *high_scores = loaded,
+        Err(error) => warn!("ignoring broken {}: {}", HIGH_SCORES_PATH, error),
+    }
+}
+
+fn save_high_scores(high_scores: Res<HighScores>) {
+    match ron::ser::to_string_pretty(&*high_scores, default()) {
+        Ok(contents) => {
+            write_file(HIGH_SCORES_PATH, &contents);
+        }
+        Err(error) => warn!("could not save high scores: {}", error),
+    }
+}
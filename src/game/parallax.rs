@@ -0,0 +1,222 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::Rng;
+
+use super::{tuning::Tuning, GameState};
+use crate::{
+    accessibility::Accessibility,
+    viewport::{ARENA_HEIGHT, ARENA_WIDTH},
+};
+
+const LAYER_WIDTH: u32 = ARENA_WIDTH as u32; // each strip tiles with itself
+
+#[derive(Clone, Copy)]
+enum LayerShape {
+    FarClouds,
+    Hills,
+    Trees,
+}
+
+// one strip of scenery behind the arena, nearer strips drift faster
+struct ParallaxLayer {
+    shape: LayerShape,
+    height: u32,
+    top: bool,  // hangs from the top of the arena instead of standing on the bottom
+    speed: f32, // share of the cloud speed
+    z: f32,
+    color: Color,
+}
+
+const LAYERS: [ParallaxLayer; 3] = [
+    ParallaxLayer {
+        shape: LayerShape::FarClouds,
+        height: 50,
+        top: true,
+        speed: 0.15,
+        z: -0.09,
+        color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+    },
+    ParallaxLayer {
+        shape: LayerShape::Hills,
+        height: 70,
+        top: false,
+        speed: 0.3,
+        z: -0.08,
+        color: Color::rgba(0.55, 0.35, 0.3, 0.2),
+    },
+    ParallaxLayer {
+        shape: LayerShape::Trees,
+        height: 45,
+        top: false,
+        speed: 0.6,
+        z: -0.07,
+        color: Color::rgba(0.35, 0.25, 0.25, 0.3),
+    },
+];
+
+// two of these per layer, side by side, leapfrog each other as they scroll
+#[derive(Component)]
+struct ParallaxTile {
+    speed: f32,
+}
+
+pub struct ParallaxPlugin;
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), spawn_parallax_layers)
+            .add_systems(
+                Update,
+                scroll_parallax_layers.run_if(in_state(GameState::Game)),
+            )
+            .add_systems(OnExit(GameState::Game), despawn_parallax_layers);
+    }
+}
+
+// a white silhouette, tinted with the layer's colour when drawn
+fn layer_image(layer: &ParallaxLayer) -> Image {
+    let mut rng = rand::thread_rng();
+    let width = LAYER_WIDTH as f32;
+    let height = layer.height as f32;
+    // distance along the strip, wrapping round so both ends of the strip meet
+    let wrapped = move |x: f32, centre: f32| {
+        let distance = (x - centre).abs();
+        distance.min(width - distance)
+    };
+
+    // whether a pixel is filled, with y counted from the edge the layer is attached to
+    let covered: Box<dyn Fn(f32, f32) -> bool> = match layer.shape {
+        LayerShape::FarClouds => {
+            let clouds: Vec<(Vec2, Vec2)> = (0..rng.gen_range(4..7))
+                .map(|_| {
+                    let centre =
+                        Vec2::new(rng.gen_range(0.0..width), rng.gen_range(0.3..0.7) * height);
+                    let radius = Vec2::new(rng.gen_range(15.0..30.0), rng.gen_range(5.0..10.0));
+                    (centre, radius)
+                })
+                .collect();
+            Box::new(move |x, y| {
+                clouds.iter().any(|&(centre, radius)| {
+                    (Vec2::new(wrapped(x, centre.x), y - centre.y) / radius).length_squared() < 1.0
+                })
+            })
+        }
+        LayerShape::Hills => {
+            // whole numbers of waves across the strip so it tiles
+            let waves: Vec<(f32, f32)> = [1.0, 2.0, 5.0]
+                .into_iter()
+                .map(|frequency| (frequency, rng.gen_range(0.0..TAU)))
+                .collect();
+            Box::new(move |x, y| {
+                let wave: f32 = waves
+                    .iter()
+                    .map(|(frequency, phase)| {
+                        (x / width * TAU * frequency + phase).sin() / frequency
+                    })
+                    .sum();
+                y < height * (0.55 + 0.25 * wave)
+            })
+        }
+        LayerShape::Trees => {
+            let trees: Vec<(f32, f32, f32)> = (0..rng.gen_range(8..14))
+                .map(|_| {
+                    let tree_height = rng.gen_range(0.5..1.0) * height;
+                    let half_width = tree_height * rng.gen_range(0.2..0.3);
+                    (rng.gen_range(0.0..width), tree_height, half_width)
+                })
+                .collect();
+            // pines on trunks, along a strip of ground
+            Box::new(move |x, y| {
+                y < 3.0
+                    || trees.iter().any(|&(centre, tree_height, half_width)| {
+                        let distance = wrapped(x, centre);
+                        y < tree_height * (1.0 - distance / half_width)
+                            || (distance < 1.0 && y < 6.0)
+                    })
+            })
+        }
+    };
+
+    let mut data = Vec::with_capacity((LAYER_WIDTH * layer.height * 4) as usize);
+    // image rows run from the top down
+    for row in 0..layer.height {
+        let y = if layer.top {
+            row
+        } else {
+            layer.height - 1 - row
+        };
+        for x in 0..LAYER_WIDTH {
+            let alpha = if covered(x as f32 + 0.5, y as f32 + 0.5) {
+                255
+            } else {
+                0
+            };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: LAYER_WIDTH,
+            height: layer.height,
+            ..default()
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn spawn_parallax_layers(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    for layer in &LAYERS {
+        let texture = images.add(layer_image(layer));
+        let half_height = layer.height as f32 / 2.0;
+        let y = if layer.top {
+            ARENA_HEIGHT / 2.0 - half_height
+        } else {
+            half_height - ARENA_HEIGHT / 2.0
+        };
+
+        for x in [0.0, ARENA_WIDTH] {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: layer.color,
+                        ..default()
+                    },
+                    texture: texture.clone(),
+                    transform: Transform::from_xyz(x, y, layer.z),
+                    ..default()
+                },
+                ParallaxTile { speed: layer.speed },
+            ));
+        }
+    }
+}
+
+fn scroll_parallax_layers(
+    mut tile_query: Query<(&mut Transform, &ParallaxTile)>,
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    accessibility: Res<Accessibility>,
+) {
+    if accessibility.reduced_motion {
+        return;
+    }
+
+    for (mut transform, tile) in &mut tile_query {
+        transform.translation.x -= tile.speed * tuning.cloud_speed * time.delta_seconds();
+        if transform.translation.x <= -ARENA_WIDTH {
+            transform.translation.x += 2.0 * ARENA_WIDTH;
+        }
+    }
+}
+
+fn despawn_parallax_layers(mut commands: Commands, tile_query: Query<Entity, With<ParallaxTile>>) {
+    for entity in &tile_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,281 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{GameState, SimulationState};
+use crate::display::DisplaySettings;
+
+const PARTICLE_Z: f32 = 0.5;
+const MAX_PARTICLES: usize = 512;
+
+// how a burst of particles looks and moves, from birth to death
+#[derive(Clone, Copy)]
+pub struct ParticleEffect {
+    pub count: u32, // at high quality
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub spread: f32, // radians either side of the emitter's direction
+    pub gravity: f32,
+    pub color: (Color, Color),
+    pub size: (f32, f32),
+}
+
+pub const BUG_BURST: ParticleEffect = ParticleEffect {
+    count: 12,
+    lifetime: (0.25, 0.5),
+    speed: (30.0, 70.0),
+    spread: PI,
+    gravity: 60.0,
+    color: (Color::rgb(1.0, 0.6, 0.1), Color::rgba(0.6, 0.2, 0.1, 0.0)),
+    size: (3.0, 1.0),
+};
+
+pub const LANDING_DUST: ParticleEffect = ParticleEffect {
+    count: 8,
+    lifetime: (0.2, 0.4),
+    speed: (10.0, 30.0),
+    spread: PI / 2.0,
+    gravity: -10.0, // dust drifts up
+    color: (
+        Color::rgba(0.8, 0.75, 0.65, 0.8),
+        Color::rgba(0.8, 0.75, 0.65, 0.0),
+    ),
+    size: (2.0, 4.0),
+};
+
+pub const FOOTSTEP_DUST: ParticleEffect = ParticleEffect {
+    count: 2,
+    lifetime: (0.15, 0.3),
+    speed: (5.0, 15.0),
+    spread: PI / 2.0,
+    gravity: -10.0,
+    color: (
+        Color::rgba(0.8, 0.75, 0.65, 0.6),
+        Color::rgba(0.8, 0.75, 0.65, 0.0),
+    ),
+    size: (1.5, 3.0),
+};
+
+pub const BLOCK_DEBRIS: ParticleEffect = ParticleEffect {
+    count: 10,
+    lifetime: (0.4, 0.7),
+    speed: (20.0, 60.0),
+    spread: PI / 2.0,
+    gravity: 200.0,
+    color: (Color::rgb(0.55, 0.4, 0.3), Color::rgba(0.4, 0.3, 0.2, 0.0)),
+    size: (3.0, 2.0),
+};
+
+pub const MUZZLE_FLASH: ParticleEffect = ParticleEffect {
+    count: 6,
+    lifetime: (0.05, 0.12),
+    speed: (40.0, 90.0),
+    spread: PI / 6.0,
+    gravity: 0.0,
+    color: (Color::rgb(1.0, 1.0, 0.7), Color::rgba(1.0, 0.5, 0.0, 0.0)),
+    size: (2.5, 1.0),
+};
+
+pub const RAIN: ParticleEffect = ParticleEffect {
+    count: 2,
+    lifetime: (1.0, 1.1),
+    speed: (160.0, 190.0),
+    spread: 0.05,
+    gravity: 0.0,
+    color: (
+        Color::rgba(0.6, 0.7, 1.0, 0.7),
+        Color::rgba(0.6, 0.7, 1.0, 0.3),
+    ),
+    size: (1.0, 1.0),
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParticleQuality {
+    Off,
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+impl ParticleQuality {
+    pub fn next(&self) -> Self {
+        match self {
+            ParticleQuality::Off => ParticleQuality::Low,
+            ParticleQuality::Low => ParticleQuality::Medium,
+            ParticleQuality::Medium => ParticleQuality::High,
+            ParticleQuality::High => ParticleQuality::Off,
+        }
+    }
+
+    fn density(&self) -> f32 {
+        match self {
+            ParticleQuality::Off => 0.0,
+            ParticleQuality::Low => 0.25,
+            ParticleQuality::Medium => 0.5,
+            ParticleQuality::High => 1.0,
+        }
+    }
+}
+
+// a one shot burst, replaced by its particles the frame after it is spawned
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    pub direction: Vec2,
+}
+
+#[derive(Component)]
+struct Particle {
+    age: f32,
+    lifetime: f32,
+    velocity: Vec2,
+    effect: ParticleEffect,
+}
+
+// hidden particles waiting to be reused
+#[derive(Resource, Default)]
+struct ParticlePool {
+    free: Vec<Entity>,
+    total: usize,
+}
+
+pub struct ParticlesPlugin;
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticlePool>()
+            .add_systems(OnExit(GameState::Game), recycle_all_particles)
+            .add_systems(PostUpdate, emit_particles.run_if(in_state(GameState::Game)))
+            .add_systems(
+                Update,
+                update_particles
+                    .run_if(in_state(GameState::Game))
+                    .run_if(in_state(SimulationState::Running)),
+            );
+    }
+}
+
+pub fn spawn_emitter(
+    commands: &mut Commands,
+    effect: ParticleEffect,
+    position: Vec2,
+    direction: Vec2,
+) {
+    commands.spawn((
+        ParticleEmitter { effect, direction },
+        TransformBundle::from_transform(Transform::from_translation(position.extend(PARTICLE_Z))),
+    ));
+}
+
+// particles are only for show, so they use their own randomness and leave GameRng alone
+fn emit_particles(
+    mut commands: Commands,
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(&mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
+    emitter_query: Query<(Entity, &ParticleEmitter, &Transform), Without<Particle>>,
+    display: Res<DisplaySettings>,
+) {
+    let mut rng = rand::thread_rng();
+    let density = display.particle_quality.density();
+
+    for (emitter_entity, emitter, emitter_transform) in &emitter_query {
+        commands.entity(emitter_entity).despawn();
+
+        let effect = emitter.effect;
+        let count = (effect.count as f32 * density).round() as u32;
+        let base_angle = emitter.direction.y.atan2(emitter.direction.x);
+
+        for _ in 0..count {
+            let angle = base_angle + rng.gen_range(-effect.spread..=effect.spread);
+            let particle = Particle {
+                age: 0.0,
+                lifetime: rng.gen_range(effect.lifetime.0..=effect.lifetime.1),
+                velocity: Vec2::from_angle(angle) * rng.gen_range(effect.speed.0..=effect.speed.1),
+                effect,
+            };
+            let translation = emitter_transform.translation;
+
+            if let Some(entity) = pool.free.pop() {
+                if let Ok((mut old, mut transform, mut sprite, mut visibility)) =
+                    particle_query.get_mut(entity)
+                {
+                    *old = particle;
+                    transform.translation = translation;
+                    // still dressed as the effect it last played until update_particles runs
+                    sprite.color = effect.color.0;
+                    sprite.custom_size = Some(Vec2::splat(effect.size.0));
+                    *visibility = Visibility::Inherited;
+                }
+            } else if pool.total < MAX_PARTICLES {
+                pool.total += 1;
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: effect.color.0,
+                            custom_size: Some(Vec2::splat(effect.size.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(translation),
+                        ..default()
+                    },
+                    particle,
+                ));
+            }
+        }
+    }
+}
+
+// colour and size blend from the start to the end of the effect over each particle's life
+fn update_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in &mut particle_query {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        particle.age += time.delta_seconds();
+        if particle.age >= particle.lifetime {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+            continue;
+        }
+
+        let effect = particle.effect;
+        particle.velocity.y -= effect.gravity * time.delta_seconds();
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.0);
+
+        let t = particle.age / particle.lifetime;
+        let (start, end) = effect.color;
+        sprite.color = Color::rgba(
+            start.r() + (end.r() - start.r()) * t,
+            start.g() + (end.g() - start.g()) * t,
+            start.b() + (end.b() - start.b()) * t,
+            start.a() + (end.a() - start.a()) * t,
+        );
+        sprite.custom_size = Some(Vec2::splat(
+            effect.size.0 + (effect.size.1 - effect.size.0) * t,
+        ));
+    }
+}
+
+fn recycle_all_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(Entity, &mut Visibility), With<Particle>>,
+) {
+    for (entity, mut visibility) in &mut particle_query {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+}
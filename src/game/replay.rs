@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bugs::reset_bug_spawn_timer,
+    controlls::{CatInput, CatInputSet},
+    difficulty::DifficultyConfig,
+    modes::GameMode,
+    reset_stats,
+    rng::{reseed, FixedSeed, RunSeed},
+    touch::touch_cat_input,
+    weather::{start_weather, CurrentWeather, WeatherSettings},
+    CoopMode, GameState, Player, SimulationState,
+};
+use crate::{
+    cli::LaunchOptions,
+    loading::AfterLoading,
+    persistence::{read_file, write_file},
+};
+
+// the input of every player in one frame and how long that frame took in real time,
+// so the hit-stop freezes play back the same
+#[derive(Serialize, Deserialize)]
+struct ReplayFrame {
+    delta: f32,
+    inputs: Vec<CatInput>, // indexed by Player::index
+    // pausing also happens through the menus and unplugged gamepads, not just CatInput
+    #[serde(default)]
+    simulation: Option<SimulationState>,
+}
+
+// everything needed to play a run again, the bugs follow from the seed
+#[derive(Serialize, Deserialize)]
+struct Replay {
+    seed: u64,
+    mode: GameMode,
+    coop_mode: CoopMode,
+    difficulty: DifficultyConfig,
+    #[serde(default)]
+    weather: bool, // older recordings were made before there was weather
+    frames: Vec<ReplayFrame>,
+}
+
+// --record, each run overwrites the file
+#[derive(Resource)]
+struct Recording {
+    path: String,
+    replay: Option<Replay>,
+}
+
+// --replay, removed once every frame has been played
+#[derive(Resource)]
+struct Playback {
+    replay: Replay,
+    next_frame: usize,
+    time_strategy: TimeUpdateStrategy, // put back when the replay ends
+}
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (start_recording, load_replay))
+            .add_systems(
+                OnEnter(GameState::Game),
+                (
+                    begin_recorded_run
+                        .after(reseed)
+                        .after(start_weather)
+                        .run_if(resource_exists::<Recording>()),
+                    apply_replay_settings
+                        .before(reset_stats)
+                        .before(reset_bug_spawn_timer)
+                        .before(start_weather)
+                        .run_if(resource_exists::<Playback>()),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::Game),
+                save_recording.run_if(resource_exists::<Recording>()),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    record_frame.run_if(resource_exists::<Recording>()),
+                    play_frame.run_if(resource_exists::<Playback>()),
+                )
+                    .after(CatInputSet)
+                    .after(touch_cat_input)
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                PostUpdate,
+                record_simulation_state
+                    .run_if(resource_exists::<Recording>())
+                    .run_if(in_state(GameState::Game)),
+            )
+            // closing the window mid run still keeps the recording
+            .add_systems(
+                Last,
+                save_recording
+                    .run_if(on_event::<AppExit>())
+                    .run_if(resource_exists::<Recording>())
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
+
+fn start_recording(mut commands: Commands, options: Res<LaunchOptions>) {
+    if let Some(path) = &options.record {
+        commands.insert_resource(Recording {
+            path: path.clone(),
+            replay: None,
+        });
+    }
+}
+
+fn load_replay(
+    mut commands: Commands,
+    mut fixed_seed: ResMut<FixedSeed>,
+    mut mode: ResMut<GameMode>,
+    mut coop_mode: ResMut<CoopMode>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut after_loading: ResMut<AfterLoading>,
+    options: Res<LaunchOptions>,
+) {
+    let Some(path) = &options.replay else {
+        return;
+    };
+    let Some(contents) = read_file(path) else {
+        error!("could not read the replay {}", path);
+        return;
+    };
+    let replay: Replay = match ron::from_str(&contents) {
+        Ok(replay) => replay,
+        Err(error) => {
+            error!("broken replay {}: {}", path, error);
+            return;
+        }
+    };
+    let Some(first_frame) = replay.frames.first() else {
+        error!("the replay {} has no frames", path);
+        return;
+    };
+
+    info!("playing {} frames from {}", replay.frames.len(), path);
+    fixed_seed.0 = Some(replay.seed);
+    *mode = replay.mode;
+    *coop_mode = replay.coop_mode;
+    let previous_strategy = std::mem::replace(
+        &mut *time_strategy,
+        TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(first_frame.delta)),
+    );
+    after_loading.0 = GameState::Game;
+
+    commands.insert_resource(Playback {
+        replay,
+        next_frame: 0,
+        time_strategy: previous_strategy,
+    });
+}
+
+// the replay brings its own difficulty and weather, without touching the saved settings
+fn apply_replay_settings(
+    mut config: ResMut<DifficultyConfig>,
+    mut weather_settings: ResMut<WeatherSettings>,
+    playback: Res<Playback>,
+) {
+    *config = playback.replay.difficulty;
+    weather_settings.set(playback.replay.mode, playback.replay.weather);
+}
+
+fn begin_recorded_run(
+    mut recording: ResMut<Recording>,
+    run_seed: Res<RunSeed>,
+    mode: Res<GameMode>,
+    coop_mode: Res<CoopMode>,
+    config: Res<DifficultyConfig>,
+    weather: Res<CurrentWeather>,
+) {
+    recording.replay = Some(Replay {
+        seed: run_seed.0,
+        mode: *mode,
+        coop_mode: *coop_mode,
+        difficulty: *config,
+        weather: weather.enabled,
+        frames: Vec::new(),
+    });
+}
+
+fn record_frame(
+    mut recording: ResMut<Recording>,
+    cat_query: Query<(&Player, &CatInput)>,
+    time: Res<Time<Real>>,
+) {
+    let Some(replay) = &mut recording.replay else {
+        return;
+    };
+
+    let mut inputs = vec![CatInput::default(); replay.coop_mode.players().len()];
+    for (player, cat_input) in &cat_query {
+        if let Some(input) = inputs.get_mut(player.index()) {
+            *input = cat_input.clone();
+        }
+    }
+
+    replay.frames.push(ReplayFrame {
+        delta: time.delta_seconds(),
+        inputs,
+        simulation: None,
+    });
+}
+
+// the state the frame's systems actually ran in, which is only settled after PreUpdate
+fn record_simulation_state(
+    mut recording: ResMut<Recording>,
+    simulation_state: Res<State<SimulationState>>,
+) {
+    let Some(frame) = recording
+        .replay
+        .as_mut()
+        .and_then(|replay| replay.frames.last_mut())
+    else {
+        return;
+    };
+    frame.simulation = Some(*simulation_state.get());
+}
+
+fn save_recording(mut recording: ResMut<Recording>) {
+    let Some(replay) = recording.replay.take() else {
+        return;
+    };
+
+    match ron::to_string(&replay) {
+        Ok(contents) => {
+            if write_file(&recording.path, &contents) {
+                info!(
+                    "recorded {} frames to {}",
+                    replay.frames.len(),
+                    recording.path
+                );
+            }
+        }
+        Err(error) => warn!("could not save the recording: {}", error),
+    }
+}
+
+fn play_frame(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    mut cat_query: Query<(&Player, &mut CatInput)>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut next_simulation_state: ResMut<NextState<SimulationState>>,
+) {
+    let Some(frame) = playback.replay.frames.get(playback.next_frame) else {
+        return;
+    };
+
+    // overrides whatever paused or resumed the run during the previous frame
+    if let Some(simulation) = frame.simulation {
+        next_simulation_state.set(simulation);
+    }
+
+    for (player, mut cat_input) in &mut cat_query {
+        *cat_input = frame
+            .inputs
+            .get(player.index())
+            .cloned()
+            .unwrap_or_default();
+    }
+
+    playback.next_frame += 1;
+    match playback.replay.frames.get(playback.next_frame) {
+        Some(next_frame) => {
+            *time_strategy =
+                TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(next_frame.delta));
+        }
+        None => {
+            info!("replay finished");
+            *time_strategy = std::mem::take(&mut playback.time_strategy);
+            commands.remove_resource::<Playback>();
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::GameState;
+use crate::cli::LaunchOptions;
+
+// the randomness gameplay depends on, reseeded at the start of every run so a
+// seed and the same inputs give the same run
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+// the seed of the current run, written into recordings
+#[derive(Resource, Default)]
+pub struct RunSeed(pub u64);
+
+// every run uses this seed when set, from --seed or a replay
+#[derive(Resource, Default)]
+pub struct FixedSeed(pub Option<u64>);
+
+pub struct RngPlugin;
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng(StdRng::from_entropy()))
+            .init_resource::<RunSeed>()
+            .init_resource::<FixedSeed>()
+            .add_systems(Startup, read_seed_option)
+            .add_systems(OnEnter(GameState::Game), reseed);
+    }
+}
+
+fn read_seed_option(mut fixed_seed: ResMut<FixedSeed>, options: Res<LaunchOptions>) {
+    if options.seed.is_some() {
+        fixed_seed.0 = options.seed;
+    }
+}
+
+pub fn reseed(mut rng: ResMut<GameRng>, mut run_seed: ResMut<RunSeed>, fixed_seed: Res<FixedSeed>) {
+    run_seed.0 = fixed_seed.0.unwrap_or_else(rand::random);
+    rng.0 = StdRng::seed_from_u64(run_seed.0);
+}
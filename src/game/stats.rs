@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+pub use super::bugs::{BugSpecies, BUG_SPECIES};
+use super::{
+    bugs::{BugKilledEvent, BulletHitEvent, HeartLostEvent},
+    bullet::BulletFireEvent,
+    cat::CatJumpEvent,
+    ground::GroundBuildEvent,
+    DifficultyMultiplier, GameState, Score, SimulationState,
+};
+use crate::{locale::Locale, menu::toast::ToastEvent, persistence::write_file};
+
+const RUN_STATS_PATH: &str = "run_stats.json";
+
+// everything that happened during the current run, kept around for the game over screen
+#[derive(Resource, Default, Serialize)]
+pub struct RunStats {
+    pub bugs_killed: BTreeMap<BugSpecies, u32>,
+    pub hearts_lost: BTreeMap<BugSpecies, u32>, // keyed by the bug that got away
+    pub shots_fired: u32,
+    pub bullet_hits: u32,
+    pub blocks_placed: u32,
+    pub jumps: u32,
+    pub peak_difficulty: f32,
+    pub run_time: f32, // seconds spent unpaused
+}
+impl RunStats {
+    pub fn kills(&self, species: BugSpecies) -> u32 {
+        self.bugs_killed.get(&species).copied().unwrap_or(0)
+    }
+
+    pub fn escapes(&self, species: BugSpecies) -> u32 {
+        self.hearts_lost.get(&species).copied().unwrap_or(0)
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        self.bullet_hits as f32 / self.shots_fired as f32
+    }
+}
+
+// the exported file, stats plus the numbers derived from them
+#[derive(Serialize)]
+struct RunReport<'a> {
+    score: u32,
+    accuracy: f32,
+    #[serde(flatten)]
+    stats: &'a RunStats,
+}
+
+#[derive(Event)]
+pub struct ExportRunStatsEvent;
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_event::<ExportRunStatsEvent>()
+            .add_systems(OnEnter(GameState::Game), reset_run_stats)
+            .add_systems(
+                Update,
+                (
+                    count_kills.run_if(on_event::<BugKilledEvent>()),
+                    count_hearts_lost.run_if(on_event::<HeartLostEvent>()),
+                    count_shots.run_if(on_event::<BulletFireEvent>()),
+                    count_hits.run_if(on_event::<BulletHitEvent>()),
+                    count_blocks.run_if(on_event::<GroundBuildEvent>()),
+                    count_jumps.run_if(on_event::<CatJumpEvent>()),
+                    track_peak_difficulty.run_if(resource_changed::<DifficultyMultiplier>()),
+                    track_run_time.run_if(in_state(SimulationState::Running)),
+                )
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                Update,
+                export_run_stats.run_if(on_event::<ExportRunStatsEvent>()),
+            );
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn count_kills(mut kill_reader: EventReader<BugKilledEvent>, mut stats: ResMut<RunStats>) {
+    for BugKilledEvent(species) in kill_reader.read() {
+        *stats.bugs_killed.entry(*species).or_default() += 1;
+    }
+}
+
+fn count_hearts_lost(mut heart_reader: EventReader<HeartLostEvent>, mut stats: ResMut<RunStats>) {
+    for HeartLostEvent(species) in heart_reader.read() {
+        *stats.hearts_lost.entry(*species).or_default() += 1;
+    }
+}
+
+fn count_shots(mut fire_reader: EventReader<BulletFireEvent>, mut stats: ResMut<RunStats>) {
+    stats.shots_fired += fire_reader.read().count() as u32;
+}
+
+fn count_hits(mut hit_reader: EventReader<BulletHitEvent>, mut stats: ResMut<RunStats>) {
+    stats.bullet_hits += hit_reader.read().count() as u32;
+}
+
+fn count_blocks(mut build_reader: EventReader<GroundBuildEvent>, mut stats: ResMut<RunStats>) {
+    stats.blocks_placed += build_reader.read().count() as u32;
+}
+
+fn count_jumps(mut jump_reader: EventReader<CatJumpEvent>, mut stats: ResMut<RunStats>) {
+    stats.jumps += jump_reader.read().count() as u32;
+}
+
+fn track_peak_difficulty(mut stats: ResMut<RunStats>, difficulty: Res<DifficultyMultiplier>) {
+    stats.peak_difficulty = stats.peak_difficulty.max(difficulty.0);
+}
+
+fn track_run_time(mut stats: ResMut<RunStats>, time: Res<Time>) {
+    stats.run_time += time.delta_seconds();
+}
+
+// the web build cannot write files, there the report goes to the browser console
+fn export_run_stats(
+    mut toast_writer: EventWriter<ToastEvent>,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    locale: Res<Locale>,
+) {
+    let report = RunReport {
+        score: score.0,
+        accuracy: stats.accuracy(),
+        stats: &stats,
+    };
+
+    let contents = match serde_json::to_string_pretty(&report) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("could not export run stats: {}", error);
+            return;
+        }
+    };
+
+    if write_file(RUN_STATS_PATH, &contents) {
+        toast_writer.send(ToastEvent(
+            locale.format("stats-exported", &[&RUN_STATS_PATH]),
+        ));
+    } else {
+        info!("run stats: {}", contents);
+        toast_writer.send(ToastEvent(locale.get("stats-logged")));
+    }
+}
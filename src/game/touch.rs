@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use super::{
+    controlls::{CatAction, CatInput, CatInputSet},
+    GameState, Player,
+};
+use crate::locale::{Locale, LocalizedText};
+
+const TOUCH_BUTTON_SIZE: f32 = 64.0;
+const TOUCH_BUTTON_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.4);
+const TOUCH_PRESSED_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.7);
+
+#[derive(Resource, Default)]
+pub struct TouchControlls {
+    pub detected: bool, // set by the first touch, the controlls stay hidden until then
+    pub hidden: bool,   // the player turned them off in settings
+}
+impl TouchControlls {
+    fn visible(&self) -> bool {
+        self.detected && !self.hidden
+    }
+}
+
+#[derive(Component)]
+struct TouchOverlay;
+
+#[derive(Component)]
+pub struct TouchButton(CatAction);
+
+pub struct TouchPlugin;
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControlls>()
+            .add_systems(PreUpdate, touch_cat_input.after(CatInputSet))
+            .add_systems(OnEnter(GameState::Game), spawn_touch_overlay)
+            .add_systems(OnExit(GameState::Game), despawn_touch_overlay)
+            .add_systems(
+                Update,
+                (
+                    detect_touch,
+                    (despawn_touch_overlay, spawn_touch_overlay)
+                        .chain()
+                        .run_if(resource_changed::<TouchControlls>())
+                        .run_if(in_state(GameState::Game)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn detect_touch(touches: Res<Touches>, mut touch_controlls: ResMut<TouchControlls>) {
+    if !touch_controlls.detected && touches.any_just_pressed() {
+        touch_controlls.detected = true;
+    }
+}
+
+fn spawn_touch_overlay(
+    mut commands: Commands,
+    touch_controlls: Res<TouchControlls>,
+    locale: Res<Locale>,
+) {
+    if !touch_controlls.visible() {
+        return;
+    }
+
+    let overlay_style = Style {
+        position_type: PositionType::Absolute,
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::End,
+        justify_content: JustifyContent::SpaceBetween,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        padding: UiRect::all(Val::Px(15.0)),
+        ..default()
+    };
+
+    let cluster_style = Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::End,
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: overlay_style,
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            TouchOverlay,
+        ))
+        .with_children(|parent| {
+            // d-pad
+            parent
+                .spawn(NodeBundle {
+                    style: cluster_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    attach_touch_button(parent, CatAction::Left, "<");
+                    attach_touch_button(parent, CatAction::Up, "^");
+                    attach_touch_button(parent, CatAction::Right, ">");
+                });
+
+            attach_touch_button(parent, CatAction::Pause, "||");
+
+            parent
+                .spawn(NodeBundle {
+                    style: cluster_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    attach_labelled_touch_button(
+                        parent,
+                        CatAction::PlaceBlock,
+                        "touch-block",
+                        &locale,
+                    );
+                    attach_labelled_touch_button(
+                        parent,
+                        CatAction::ToggleWeapon,
+                        "touch-gun",
+                        &locale,
+                    );
+                    attach_labelled_touch_button(parent, CatAction::Fire, "touch-fire", &locale);
+                    attach_labelled_touch_button(parent, CatAction::Jump, "touch-jump", &locale);
+                });
+        });
+}
+
+// a button with a symbol for a label, the same in every language
+fn attach_touch_button(parent: &mut ChildBuilder, action: CatAction, symbol: &str) {
+    spawn_touch_button(parent, action, symbol, None);
+}
+
+fn attach_labelled_touch_button(
+    parent: &mut ChildBuilder,
+    action: CatAction,
+    message_id: &'static str,
+    locale: &Locale,
+) {
+    spawn_touch_button(parent, action, &locale.get(message_id), Some(message_id));
+}
+
+fn spawn_touch_button(
+    parent: &mut ChildBuilder,
+    action: CatAction,
+    label: &str,
+    message_id: Option<&'static str>,
+) {
+    let button_style = Style {
+        width: Val::Px(TOUCH_BUTTON_SIZE),
+        height: Val::Px(TOUCH_BUTTON_SIZE),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    parent
+        .spawn((
+            NodeBundle {
+                style: button_style,
+                background_color: TOUCH_BUTTON_COLOR.into(),
+                ..default()
+            },
+            TouchButton(action),
+        ))
+        .with_children(|parent| {
+            let mut text = parent.spawn(TextBundle {
+                text: Text::from_section(label, text_style),
+                ..default()
+            });
+
+            if let Some(message_id) = message_id {
+                text.insert(LocalizedText(message_id));
+            }
+        });
+}
+
+fn despawn_touch_overlay(mut commands: Commands, query: Query<Entity, With<TouchOverlay>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// touches act as player one's buttons, on top of whatever their other devices pressed
+pub fn touch_cat_input(
+    mut cat_query: Query<(&Player, &mut CatInput)>,
+    mut button_query: Query<(&TouchButton, &Node, &GlobalTransform, &mut BackgroundColor)>,
+    mut held_last_frame: Local<HashSet<CatAction>>,
+    touches: Res<Touches>,
+) {
+    let mut held = HashSet::new();
+
+    for (touch_button, node, transform, mut background) in &mut button_query {
+        let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+        let is_touched = touches.iter().any(|touch| rect.contains(touch.position()));
+
+        if is_touched {
+            held.insert(touch_button.0);
+        }
+        background.0 = if is_touched {
+            TOUCH_PRESSED_COLOR
+        } else {
+            TOUCH_BUTTON_COLOR
+        };
+    }
+
+    for (&player, mut cat_input) in &mut cat_query {
+        if player != Player::One {
+            continue;
+        }
+        for &action in &held {
+            cat_input.press(action, !held_last_frame.contains(&action));
+        }
+    }
+
+    *held_last_frame = held;
+}
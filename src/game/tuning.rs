@@ -0,0 +1,126 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+const TUNING_PATH: &str = "tuning.ron";
+
+// physics and balance numbers, read from assets/tuning.ron and reloaded while
+// the game runs when built with the hot_reload feature
+#[derive(Asset, Resource, TypePath, Clone, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    pub gravity: f32,
+    pub friction: f32, // share of the horizontal velocity kept every frame
+    pub cat_speed: f32,
+    pub cat_jump_force: f32,
+    pub cat_gun_weight: f32, // subtracts from jump force when gun is equiped
+    pub cat_stomp_bounce: f32,
+    pub bullet_speed: f32,
+    pub bug_vertical_speed: f32,
+    pub bug_vertical_interval: (f32, f32), // seconds between direction changes of flying bugs
+    pub cloud_speed: f32,
+    pub cloud_spawn_rate: f32,
+    pub max_temp_ground: usize,
+    pub flora_spawn_rate: f32, // chance of a plant on each ground tile
+    pub day_length: f32,       // seconds for a whole day and night
+    pub weather_duration: (f32, f32), // seconds before the weather may change
+    pub wind_strength: f32,
+    pub rain_friction: f32, // friction on wet ground, closer to 1 is more slippery
+    pub lightning_interval: (f32, f32), // seconds between strikes in a storm
+}
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            gravity: 200.8,
+            friction: 0.8,
+            cat_speed: 25.0,
+            cat_jump_force: 80.0,
+            cat_gun_weight: 10.0,
+            cat_stomp_bounce: 50.0,
+            bullet_speed: 400.0,
+            bug_vertical_speed: 15.0,
+            bug_vertical_interval: (1.0, 2.0),
+            cloud_speed: 15.0,
+            cloud_spawn_rate: 0.69,
+            max_temp_ground: 4,
+            flora_spawn_rate: 0.12,
+            day_length: 180.0,
+            weather_duration: (20.0, 40.0),
+            wind_strength: 20.0,
+            rain_friction: 0.93,
+            lightning_interval: (2.0, 5.0),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+#[derive(Default)]
+struct TuningLoader;
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Tuning, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+pub struct TuningPlugin;
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        // the defaults are used until the file is loaded or if it is missing
+        app.init_resource::<Tuning>()
+            .init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(
+                PreUpdate,
+                apply_tuning.run_if(on_event::<AssetEvent<Tuning>>()),
+            );
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut asset_events: EventReader<AssetEvent<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+    assets: Res<Assets<Tuning>>,
+    handle: Res<TuningHandle>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = *event else {
+            continue;
+        };
+        if id != handle.0.id() {
+            continue;
+        }
+
+        if let Some(loaded) = assets.get(id) {
+            *tuning = loaded.clone();
+            info!("loaded {}", TUNING_PATH);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    camera_effects::Trauma,
+    ground::TempGround,
+    modes::GameMode,
+    particles::{spawn_emitter, BLOCK_DEBRIS, RAIN},
+    rng::{reseed, GameRng},
+    tuning::Tuning,
+    GameState, SimulationState,
+};
+use crate::{
+    locale::Locale,
+    menu::toast::ToastEvent,
+    viewport::{ARENA_HEIGHT, ARENA_WIDTH},
+};
+
+// how likely each weather is whenever it changes, adding up to 1
+const WEATHER_CHANCES: [(Weather, f32); 4] = [
+    (Weather::Clear, 0.4),
+    (Weather::Windy, 0.25),
+    (Weather::Rain, 0.2),
+    (Weather::Storm, 0.15),
+];
+const STORM_WIND: f32 = 1.5; // times the tuned wind strength
+const WIND_EASE: f32 = 0.5; // per second, so gusts build up instead of snapping
+const RAIN_PER_SECOND: f32 = 20.0; // raindrop emitters, doubled in a storm
+const LIGHTNING_RADIUS: f32 = 12.0;
+const LIGHTNING_TRAUMA: f32 = 0.5;
+const LIGHTNING_DURATION: f32 = 0.15;
+const LIGHTNING_WIDTH: f32 = 3.0;
+const LIGHTNING_Z: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Windy,
+    Rain,
+    Storm, // wind, rain and lightning
+}
+impl Weather {
+    fn message_id(&self) -> &'static str {
+        match self {
+            Weather::Clear => "weather-clear",
+            Weather::Windy => "weather-windy",
+            Weather::Rain => "weather-rain",
+            Weather::Storm => "weather-storm",
+        }
+    }
+
+    fn is_wet(&self) -> bool {
+        matches!(self, Weather::Rain | Weather::Storm)
+    }
+}
+
+// the weather of the current run, every run starts clear
+#[derive(Resource)]
+pub struct CurrentWeather {
+    pub weather: Weather,
+    pub wind: f32, // pixels per second, positive blows to the right
+    target_wind: f32,
+    pub enabled: bool, // off for modes the player turned the weather off for
+    change_timer: Timer,
+    lightning_timer: Timer,
+}
+impl Default for CurrentWeather {
+    fn default() -> Self {
+        Self {
+            weather: Weather::Clear,
+            wind: 0.0,
+            target_wind: 0.0,
+            enabled: false,
+            change_timer: Timer::default(),
+            lightning_timer: Timer::default(),
+        }
+    }
+}
+impl CurrentWeather {
+    // share of the horizontal velocity a cat on the ground keeps every frame
+    pub fn ground_friction(&self, tuning: &Tuning) -> f32 {
+        if self.weather.is_wet() {
+            tuning.rain_friction
+        } else {
+            tuning.friction
+        }
+    }
+}
+
+// the modes the player turned the weather off for, saved with the settings
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherSettings {
+    disabled_modes: BTreeSet<GameMode>,
+}
+impl WeatherSettings {
+    pub fn enabled(&self, mode: GameMode) -> bool {
+        !self.disabled_modes.contains(&mode)
+    }
+
+    pub fn set(&mut self, mode: GameMode, enabled: bool) {
+        if enabled {
+            self.disabled_modes.remove(&mode);
+        } else {
+            self.disabled_modes.insert(mode);
+        }
+    }
+
+    pub fn toggle(&mut self, mode: GameMode) {
+        self.set(mode, !self.enabled(mode));
+    }
+}
+
+// everything blown by the wind runs after this, so seeded runs play back the same
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WeatherSet;
+
+#[derive(Component)]
+struct LightningBolt(Timer);
+
+pub struct WeatherPlugin;
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentWeather>()
+            .init_resource::<WeatherSettings>()
+            .add_systems(OnEnter(GameState::Game), start_weather.after(reseed))
+            .add_systems(OnExit(GameState::Game), despawn_lightning)
+            .add_systems(
+                Update,
+                (
+                    (change_weather, strike_lightning)
+                        .chain()
+                        .in_set(WeatherSet),
+                    make_it_rain.after(WeatherSet),
+                    fade_lightning,
+                )
+                    .run_if(in_state(GameState::Game))
+                    .run_if(in_state(SimulationState::Running)),
+            );
+    }
+}
+
+fn random_duration(rng: &mut GameRng, (min, max): (f32, f32)) -> Timer {
+    Timer::from_seconds(rng.0.gen_range(min..=max), TimerMode::Once)
+}
+
+pub fn start_weather(
+    mut weather: ResMut<CurrentWeather>,
+    mut rng: ResMut<GameRng>,
+    settings: Res<WeatherSettings>,
+    mode: Res<GameMode>,
+    tuning: Res<Tuning>,
+) {
+    *weather = CurrentWeather {
+        enabled: settings.enabled(*mode),
+        ..default()
+    };
+    if weather.enabled {
+        weather.change_timer = random_duration(&mut rng, tuning.weather_duration);
+    }
+}
+
+fn change_weather(
+    mut weather: ResMut<CurrentWeather>,
+    mut rng: ResMut<GameRng>,
+    mut toast_writer: EventWriter<ToastEvent>,
+    locale: Res<Locale>,
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+) {
+    if !weather.enabled {
+        return;
+    }
+
+    let ease = (WIND_EASE * time.delta_seconds()).min(1.0);
+    weather.wind += (weather.target_wind - weather.wind) * ease;
+
+    if !weather.change_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    weather.change_timer = random_duration(&mut rng, tuning.weather_duration);
+
+    let mut roll = rng.0.gen::<f32>();
+    let next = WEATHER_CHANCES
+        .into_iter()
+        .find(|&(_, chance)| {
+            roll -= chance;
+            roll < 0.0
+        })
+        .map_or(Weather::Clear, |(weather, _)| weather);
+    if next == weather.weather {
+        return;
+    }
+
+    let direction = if rng.0.gen::<bool>() { 1.0 } else { -1.0 };
+    weather.target_wind = direction
+        * tuning.wind_strength
+        * match next {
+            Weather::Windy => 1.0,
+            Weather::Storm => STORM_WIND,
+            Weather::Clear | Weather::Rain => 0.0,
+        };
+    weather.lightning_timer = random_duration(&mut rng, tuning.lightning_interval);
+    weather.weather = next;
+    toast_writer.send(ToastEvent(locale.get(next.message_id())));
+}
+
+// a bolt somewhere along the arena burns away the blocks it hits
+fn strike_lightning(
+    mut commands: Commands,
+    mut weather: ResMut<CurrentWeather>,
+    mut rng: ResMut<GameRng>,
+    mut trauma: ResMut<Trauma>,
+    temp_ground_query: Query<(Entity, &Transform), With<TempGround>>,
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+) {
+    if weather.weather != Weather::Storm
+        || !weather.lightning_timer.tick(time.delta()).just_finished()
+    {
+        return;
+    }
+    weather.lightning_timer = random_duration(&mut rng, tuning.lightning_interval);
+
+    let x = rng.0.gen_range(-ARENA_WIDTH / 2.0..=ARENA_WIDTH / 2.0);
+    for (entity, transform) in &temp_ground_query {
+        if (transform.translation.x - x).abs() > LIGHTNING_RADIUS {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        spawn_emitter(
+            &mut commands,
+            BLOCK_DEBRIS,
+            transform.translation.truncate(),
+            Vec2::Y,
+        );
+    }
+
+    trauma.add(LIGHTNING_TRAUMA);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::new(LIGHTNING_WIDTH, ARENA_HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, 0.0, LIGHTNING_Z),
+            ..default()
+        },
+        LightningBolt(Timer::from_seconds(LIGHTNING_DURATION, TimerMode::Once)),
+    ));
+}
+
+fn fade_lightning(
+    mut commands: Commands,
+    mut bolt_query: Query<(Entity, &mut LightningBolt, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut bolt, mut sprite) in &mut bolt_query {
+        if bolt.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite.color.set_a(bolt.0.percent_left());
+    }
+}
+
+fn despawn_lightning(mut commands: Commands, bolt_query: Query<Entity, With<LightningBolt>>) {
+    for entity in &bolt_query {
+        commands.entity(entity).despawn();
+    }
+}
+
+// raindrops are only for show, so they use their own randomness and leave GameRng alone
+fn make_it_rain(mut commands: Commands, weather: Res<CurrentWeather>, time: Res<Time>) {
+    let drops_per_second = match weather.weather {
+        Weather::Rain => RAIN_PER_SECOND,
+        Weather::Storm => RAIN_PER_SECOND * 2.0,
+        Weather::Clear | Weather::Windy => return,
+    };
+
+    let mut rng = rand::thread_rng();
+    // whole drops every frame, with the chance of one more for what is left over
+    let drops = drops_per_second * time.delta_seconds();
+    let count = drops as u32 + u32::from(rng.gen::<f32>() < drops.fract());
+    let direction = Vec2::new(weather.wind / RAIN.speed.0, -1.0);
+    // drops blown in from past the edges fill the windward side
+    let drift = weather.wind.abs() * ARENA_HEIGHT / RAIN.speed.0;
+
+    for _ in 0..count {
+        let x = rng.gen_range(-ARENA_WIDTH / 2.0 - drift..=ARENA_WIDTH / 2.0 + drift);
+        spawn_emitter(
+            &mut commands,
+            RAIN,
+            Vec2::new(x, ARENA_HEIGHT / 2.0),
+            direction,
+        );
+    }
+}
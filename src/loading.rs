@@ -0,0 +1,245 @@
+use bevy::{
+    asset::{LoadState, UntypedAssetId},
+    prelude::*,
+};
+
+use crate::{
+    accessibility::Theme,
+    game::{animation::AnimationClips, stats::BUG_SPECIES},
+    locale::{Locale, LocalizedText},
+    GameState,
+};
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+const BAR_BORDER: f32 = 2.0;
+
+// every sprite the game draws, loaded before the main menu so nothing pops in during a run
+#[derive(Resource)]
+pub struct GameAssets {
+    pub cat_atlas: Handle<TextureAtlas>,
+    pub cat_clips: Handle<AnimationClips>,
+    pub bug_atlases: Vec<Handle<TextureAtlas>>, // in the same order as BUG_SPECIES
+    pub bug_clips: Handle<AnimationClips>,      // shared by every species
+    pub bullet: Handle<Image>,
+    pub bullet_trail: Handle<Image>,
+    pub clouds: Vec<Handle<Image>>,
+    pub flora: Vec<Handle<Image>>,
+    pub ground: Vec<Handle<Image>>,
+    pub temp_ground: Handle<Image>,
+}
+
+// the files behind GameAssets, watched until all of them are done
+#[derive(Resource, Default)]
+struct LoadingAssets(Vec<UntypedAssetId>);
+
+// atlases that are cut once their sheet and its .anim.ron have loaded
+struct SpriteSheet {
+    atlas: Handle<TextureAtlas>,
+    image: Handle<Image>,
+    clips: Handle<AnimationClips>,
+}
+
+#[derive(Resource, Default)]
+struct SpriteSheets(Vec<SpriteSheet>);
+
+// where the game goes once everything has loaded, --start-state and --replay change it
+#[derive(Resource)]
+pub struct AfterLoading(pub GameState);
+impl Default for AfterLoading {
+    fn default() -> Self {
+        Self(GameState::MainMenu)
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct ProgressBar;
+
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .init_resource::<SpriteSheets>()
+            .init_resource::<AfterLoading>()
+            .add_systems(Startup, load_game_assets)
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(Update, track_loading.run_if(in_state(GameState::Loading)))
+            .add_systems(
+                OnExit(GameState::Loading),
+                (cut_sprite_sheets, despawn_loading_screen),
+            );
+    }
+}
+
+fn load_game_assets(
+    mut commands: Commands,
+    mut loading: ResMut<LoadingAssets>,
+    mut sheets: ResMut<SpriteSheets>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    asset_server: Res<AssetServer>,
+) {
+    let cat_clips: Handle<AnimationClips> = asset_server.load("sprites/cat/cat.anim.ron");
+    let bug_clips: Handle<AnimationClips> = asset_server.load("sprites/bugs/bug.anim.ron");
+    loading.0.push(cat_clips.id().untyped());
+    loading.0.push(bug_clips.id().untyped());
+
+    let mut load = |path: String| {
+        let handle: Handle<Image> = asset_server.load(path);
+        loading.0.push(handle.id().untyped());
+        handle
+    };
+
+    // the handles are handed out now and filled in by cut_sprite_sheets
+    let mut sheet = |path: &str, clips: &Handle<AnimationClips>| {
+        let atlas = texture_atlases
+            .get_handle_provider()
+            .reserve_handle()
+            .typed::<TextureAtlas>();
+        sheets.0.push(SpriteSheet {
+            atlas: atlas.clone(),
+            image: load(path.to_string()),
+            clips: clips.clone(),
+        });
+        atlas
+    };
+    let cat_atlas = sheet("sprites/cat/cat_sheet_2.png", &cat_clips);
+    let bug_atlases = BUG_SPECIES
+        .iter()
+        .map(|species| sheet(species.sprite().0, &bug_clips))
+        .collect();
+
+    commands.insert_resource(GameAssets {
+        cat_atlas,
+        cat_clips,
+        bug_atlases,
+        bug_clips,
+        bullet: load("sprites/bullet/bullet.png".to_string()),
+        bullet_trail: load("sprites/bullet/bullet_trail.png".to_string()),
+        clouds: (1..=3)
+            .map(|i| load(format!("sprites/clouds/cloud_{}.png", i)))
+            .collect(),
+        flora: (1..=6)
+            .map(|i| load(format!("sprites/flora/flora_{}.png", i)))
+            .collect(),
+        ground: (1..=2)
+            .map(|i| load(format!("sprites/ground/ground_{}.png", i)))
+            .collect(),
+        temp_ground: load("sprites/ground/temp_ground_new.png".to_string()),
+    });
+}
+
+fn spawn_loading_screen(mut commands: Commands, locale: Res<Locale>, theme: Res<Theme>) {
+    let screen = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        background_color: theme.background.into(),
+        ..default()
+    };
+    let bar_frame = NodeBundle {
+        style: Style {
+            width: Val::Px(BAR_WIDTH),
+            height: Val::Px(BAR_HEIGHT),
+            border: UiRect::all(Val::Px(BAR_BORDER)),
+            ..default()
+        },
+        background_color: theme.panel.into(),
+        border_color: theme.button.into(),
+        ..default()
+    };
+    let bar = NodeBundle {
+        style: Style {
+            width: Val::Percent(0.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        background_color: Color::WHITE.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((screen, LoadingScreen))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.get("loading"),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: theme.button,
+                        ..default()
+                    },
+                ),
+                LocalizedText("loading"),
+            ));
+            parent.spawn(bar_frame).with_children(|parent| {
+                parent.spawn((bar, ProgressBar));
+            });
+        });
+}
+
+// files that failed count as done, the game goes on without them rather than hang here
+fn track_loading(
+    mut bar_query: Query<&mut Style, With<ProgressBar>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    loading: Res<LoadingAssets>,
+    after_loading: Res<AfterLoading>,
+    asset_server: Res<AssetServer>,
+) {
+    let done = loading
+        .0
+        .iter()
+        .filter(|&&id| {
+            asset_server.is_loaded_with_dependencies(id)
+                || asset_server.get_load_state(id) == Some(LoadState::Failed)
+        })
+        .count();
+
+    if let Ok(mut bar_style) = bar_query.get_single_mut() {
+        bar_style.width = Val::Percent(100.0 * done as f32 / loading.0.len().max(1) as f32);
+    }
+    if done == loading.0.len() {
+        next_state.set(after_loading.0);
+    }
+}
+
+// a sheet or .anim.ron that failed to load leaves its atlas empty, those sprites stay invisible
+fn cut_sprite_sheets(
+    mut sheets: ResMut<SpriteSheets>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    images: Res<Assets<Image>>,
+    clips: Res<Assets<AnimationClips>>,
+) {
+    for sheet in sheets.0.drain(..) {
+        let (Some(image), Some(clips)) = (images.get(&sheet.image), clips.get(&sheet.clips)) else {
+            warn!("could not cut a sprite sheet, it or its clips are missing");
+            continue;
+        };
+
+        let (tile_width, tile_height) = clips.tile_size;
+        let size = image.size();
+        let columns = (size.x / tile_width.max(1)) as usize;
+        let rows = (size.y / tile_height.max(1)) as usize;
+        let tile_size = Vec2::new(tile_width as f32, tile_height as f32);
+        // padding, offset
+        let atlas = TextureAtlas::from_grid(sheet.image, tile_size, columns, rows, None, None);
+        texture_atlases.insert(sheet.atlas, atlas);
+    }
+}
+
+fn despawn_loading_screen(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
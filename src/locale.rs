@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// the tables are compiled in so switching languages never waits on the asset server.
+// the default font only has ascii glyphs, keep translations ascii only
+const ENGLISH_TABLE: &str = include_str!("../assets/locales/en.ron");
+const GERMAN_TABLE: &str = include_str!("../assets/locales/de.ron");
+const SPANISH_TABLE: &str = include_str!("../assets/locales/es.ron");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Spanish,
+}
+impl Language {
+    pub fn next(&self) -> Self {
+        match self {
+            Language::English => Language::German,
+            Language::German => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+
+    // always shown in the language itself
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::Spanish => "Espanol",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Language::English => ENGLISH_TABLE,
+            Language::German => GERMAN_TABLE,
+            Language::Spanish => SPANISH_TABLE,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            strings: parse_table(language),
+            fallback: parse_table(Language::English),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+        self.strings = parse_table(language);
+    }
+
+    // missing messages fall back to english, then to the id itself
+    pub fn get(&self, id: &str) -> String {
+        self.strings
+            .get(id)
+            .or_else(|| self.fallback.get(id))
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    // fills each `{}` in the message with the next argument
+    pub fn format(&self, id: &str, args: &[&dyn ToString]) -> String {
+        let mut message = self.get(id);
+        for arg in args {
+            message = message.replacen("{}", &arg.to_string(), 1);
+        }
+        message
+    }
+}
+
+// marks text whose first section is the message with this id
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+pub struct LocalePlugin;
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Locale::new(Language::default()))
+            .add_systems(
+                PostUpdate,
+                update_localized_text.run_if(resource_changed::<Locale>()),
+            );
+    }
+}
+
+fn parse_table(language: Language) -> HashMap<String, String> {
+    ron::from_str(language.table()).unwrap_or_else(|error| {
+        error!("broken string table for {:?}: {}", language, error);
+        HashMap::new()
+    })
+}
+
+fn update_localized_text(mut query: Query<(&mut Text, &LocalizedText)>, locale: Res<Locale>) {
+    for (mut text, localized) in &mut query {
+        text.sections[0].value = locale.get(localized.0);
+    }
+}
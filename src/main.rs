@@ -1,70 +1,185 @@
+use std::time::Duration;
+
 use bevy::asset::AssetMetaCheck;
-use bevy::window::PresentMode;
-use bevy::{app::AppExit, prelude::*};
+use bevy::log::LogPlugin;
+use bevy::render::{settings::WgpuSettings, RenderPlugin};
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{WindowMode, WindowResolution};
+use bevy::winit::WinitPlugin;
+use bevy::{
+    app::{AppExit, PluginsState},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
 
+mod accessibility;
+mod cli;
+#[cfg(debug_assertions)]
+mod console;
+mod display;
 mod game;
+mod loading;
+mod locale;
 mod menu;
+mod persistence;
+mod viewport;
 
-use game::GamePlugin;
+use accessibility::AccessibilityPlugin;
+use cli::{LaunchOptions, StartState};
+use display::DisplayPlugin;
+use game::{modes::GameMode, GamePlugin, Heart, Score};
+use loading::{AfterLoading, LoadingPlugin};
+use locale::LocalePlugin;
 use menu::MenusPlugin;
+use persistence::PersistencePlugin;
+use viewport::ViewportPlugin;
 
-pub const SCALE_FACTOR: f32 = 4.0;
+const HEADLESS_FRAME_TIME: f32 = 1.0 / 60.0;
 
 #[derive(States, Default, Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum GameState {
     #[default]
+    Loading, // every sprite is loaded before anything else is shown
     MainMenu,
     Game,
     GameOver,
     Settings,
+    Achievements,
+    ModeSelect,
+    Restarting, // passed through for a single frame so a restart leaves and enters Game
 }
 
-#[derive(States, Default, Clone, Copy, Debug, Hash, Eq, PartialEq)]
+#[derive(States, Default, Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum SimulationState {
     #[default]
     InActive,
     Running,
     Paused,
+    Settings, // the settings menu opened over a paused run
 }
 
 fn main() {
+    let options = LaunchOptions::from_env();
+
     let custom_window = WindowPlugin {
         primary_window: Some(Window {
             title: "Tile Cat".to_string(),
+            resolution: options
+                .window
+                .map(|(width, height)| WindowResolution::new(width, height))
+                .unwrap_or_default(),
+            mode: if options.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
             fit_canvas_to_parent: true,
             canvas: Some(String::from("#bevy")),
-            present_mode: PresentMode::AutoVsync,
             prevent_default_event_handling: false,
             ..default()
         }),
         ..default()
     };
 
-    App::new()
-        .insert_resource(AssetMetaCheck::Never)
+    let mut default_plugins = DefaultPlugins
+        .set(ImagePlugin::default_nearest())
+        .set(custom_window)
+        .set(AssetPlugin {
+            mode: AssetMode::Unprocessed,
+            ..default()
+        })
+        .set(LogPlugin {
+            level: options.log_level.unwrap_or(LogPlugin::default().level),
+            ..default()
+        });
+    // no window and no gpu, the game still runs for --ticks frames
+    if options.headless {
+        default_plugins = default_plugins
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+                .into(),
+            })
+            .disable::<WinitPlugin>();
+    }
+
+    let mut app = App::new();
+    app.insert_resource(AssetMetaCheck::Never)
         .insert_resource(Msaa::Off)
+        .insert_resource(options.clone())
         .add_plugins((
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(custom_window)
-                .set(AssetPlugin {
-                    mode: AssetMode::Unprocessed,
-                    ..default()
-                }),
+            default_plugins,
+            LocalePlugin,
+            LoadingPlugin,
+            AccessibilityPlugin,
             GamePlugin,
             MenusPlugin,
+            PersistencePlugin,
+            ViewportPlugin,
+            DisplayPlugin,
         ))
         .add_state::<GameState>()
-        .add_systems(Startup, spawn_camera)
-        .add_systems(Update, exit_handler)
-        .run();
+        .add_systems(Startup, apply_start_state)
+        .add_systems(Update, exit_handler);
+
+    if let Some(ticks) = options.ticks {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            HEADLESS_FRAME_TIME,
+        )))
+        .set_runner(move |app| run_headless(app, ticks));
+    }
+
+    #[cfg(debug_assertions)]
+    app.add_plugins(console::ConsolePlugin);
+
+    app.run();
+}
+
+// --start-state and --mode skip the menus once loading is done
+fn apply_start_state(
+    mut after_loading: ResMut<AfterLoading>,
+    mut mode: ResMut<GameMode>,
+    options: Res<LaunchOptions>,
+) {
+    match options.start_state {
+        Some(StartState::Game) => after_loading.0 = GameState::Game,
+        Some(StartState::Settings) => after_loading.0 = GameState::Settings,
+        None => (),
+    }
+    if let Some(start_mode) = options.mode {
+        *mode = start_mode;
+    }
 }
 
-fn spawn_camera(mut commands: Commands) {
-    let mut camera_bundle = Camera2dBundle::default();
-    camera_bundle.projection.scale /= SCALE_FACTOR;
+// steps the app a fixed number of frames as fast as it can, then reports how the run went
+fn run_headless(mut app: App, ticks: u32) {
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let exited = |app: &App| !app.world.resource::<Events<AppExit>>().is_empty();
+    for _ in 0..ticks {
+        app.update();
+        if exited(&app) {
+            break;
+        }
+    }
+    // one more frame so whatever saves on exit gets to
+    if !exited(&app) {
+        app.world.send_event(AppExit);
+        app.update();
+    }
 
-    commands.spawn(camera_bundle);
+    info!(
+        "headless run finished in {:?} with {} points and {} hearts",
+        app.world.resource::<State<GameState>>().get(),
+        app.world.resource::<Score>().0,
+        app.world.resource::<Heart>().0,
+    );
 }
 
 fn exit_handler(key_input: Res<Input<KeyCode>>, mut exit_event_writer: EventWriter<AppExit>) {
@@ -1,60 +1,244 @@
-use crate::game::controlls::{CatAction, ControllChange};
+use crate::accessibility::{Accessibility, Theme};
+use crate::display::DisplaySettings;
+use crate::game::controlls::{
+    CatAction, ClearBindingEvent, ConflictResolution, ConflictResolvedEvent, ControllChange,
+    CycleGamepadEvent, RestoreDefaultControllsEvent,
+};
+use crate::game::{
+    difficulty::{CustomDifficulty, DifficultyParam, DifficultyPreset},
+    modes::GameMode,
+    stats::ExportRunStatsEvent,
+    touch::TouchControlls,
+    weather::WeatherSettings,
+    CoopMode, RestartEvent,
+};
+use crate::locale::{Locale, LocalizedText};
 use crate::SimulationState;
 use bevy::{app::AppExit, prelude::*};
 
-use super::{GameState, SettingsText};
+use super::{focus::MenuFocus, GameState, SettingsPage, SettingsText};
 
-const DEFUALT_BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVER_BUTTON_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
-const PRESSED_BUTTON_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+// sent for mouse clicks and for the confirm action on the focused button alike
+#[derive(Event)]
+pub struct ButtonPressEvent(pub ButtonType);
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, PartialEq)]
 pub enum ButtonType {
     Play,
+    SelectMode(GameMode),
+    ToggleWeather(GameMode),
+    CycleDifficultyPreset,
+    StepDifficulty(DifficultyParam, bool), // true steps up
     Quit,
     Settings,
+    CloseSettings,
+    Achievements,
     Resume,
+    Restart,
+    ExportStats,
     ReturnToMenu,
     SettingsButton(CatAction),
+    ClearBinding(CatAction),
+    RestoreDefaults,
+    ResolveConflict(ConflictResolution),
+    CycleGamepad,
+    CycleCoop,
+    ToggleTouchControlls,
+    CycleLanguage,
+    SettingsPage(SettingsPage),
+    CycleTextScale,
+    CyclePalette,
+    ToggleReducedMotion,
+    ToggleAutoFire,
+    CycleDisplayMode,
+    CycleResolution,
+    ToggleVsync,
+    CycleFrameCap,
+    CycleParticleQuality,
 }
 
 pub fn button_interactions(
-    mut commands: Commands,
+    mut focus: ResMut<MenuFocus>,
+    mut press_writer: EventWriter<ButtonPressEvent>,
+    query: Query<(Entity, &Interaction, &ButtonType), Changed<Interaction>>,
+) {
+    for (entity, &interaction, button_type) in &query {
+        match interaction {
+            Interaction::Hovered => focus.0 = Some(entity),
+            Interaction::Pressed => press_writer.send(ButtonPressEvent(button_type.clone())),
+            Interaction::None => (),
+        }
+    }
+}
+
+// buttons that move between screens or act on the run
+#[allow(clippy::too_many_arguments)]
+pub fn handle_button_press(
+    mut press_reader: EventReader<ButtonPressEvent>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_sim_state: ResMut<NextState<SimulationState>>,
-    mut query: Query<(&Interaction, &ButtonType, &mut BackgroundColor), Changed<Interaction>>,
+    game_state: Res<State<GameState>>,
     mut exit_event_writer: EventWriter<AppExit>,
+    mut restart_writer: EventWriter<RestartEvent>,
+    mut export_writer: EventWriter<ExportRunStatsEvent>,
+    mut coop_mode: ResMut<CoopMode>,
+    mut game_mode: ResMut<GameMode>,
+    mut difficulty_preset: ResMut<DifficultyPreset>,
+    mut custom_difficulty: ResMut<CustomDifficulty>,
+    mut weather_settings: ResMut<WeatherSettings>,
 ) {
-    for (&interaction, button_type, background) in &mut query {
-        handle_background(interaction, background);
-        if interaction == Interaction::Pressed {
-            match *button_type {
-                ButtonType::Play => next_game_state.set(GameState::Game),
-                ButtonType::Quit => exit_event_writer.send(AppExit),
-                ButtonType::Resume => next_sim_state.set(SimulationState::Running),
-                ButtonType::Settings => next_game_state.set(GameState::Settings),
-                ButtonType::ReturnToMenu => {
-                    next_game_state.set(GameState::MainMenu);
-                }
-                ButtonType::SettingsButton(action) => {
-                    commands.insert_resource(ControllChange(action));
-                }
-            };
+    for ButtonPressEvent(button_type) in press_reader.read() {
+        match *button_type {
+            ButtonType::Play => next_game_state.set(GameState::ModeSelect),
+            ButtonType::SelectMode(mode) => {
+                *game_mode = mode;
+                next_game_state.set(GameState::Game);
+            }
+            ButtonType::ToggleWeather(mode) => weather_settings.toggle(mode),
+            ButtonType::CycleDifficultyPreset => *difficulty_preset = difficulty_preset.next(),
+            ButtonType::StepDifficulty(param, up) => {
+                let (_, _, step) = param.range();
+                let value = custom_difficulty.0.get(param);
+                custom_difficulty
+                    .0
+                    .set(param, if up { value + step } else { value - step });
+            }
+            ButtonType::Quit => exit_event_writer.send(AppExit),
+            ButtonType::Resume => next_sim_state.set(SimulationState::Running),
+            ButtonType::Restart => restart_writer.send(RestartEvent),
+            ButtonType::ExportStats => export_writer.send(ExportRunStatsEvent),
+            // during a run the settings open over the paused world instead of ending it
+            ButtonType::Settings if *game_state.get() == GameState::Game => {
+                next_sim_state.set(SimulationState::Settings)
+            }
+            ButtonType::Settings => next_game_state.set(GameState::Settings),
+            ButtonType::CloseSettings if *game_state.get() == GameState::Game => {
+                next_sim_state.set(SimulationState::Paused)
+            }
+            ButtonType::CloseSettings => next_game_state.set(GameState::MainMenu),
+            ButtonType::Achievements => next_game_state.set(GameState::Achievements),
+            ButtonType::ReturnToMenu => {
+                next_game_state.set(GameState::MainMenu);
+            }
+            ButtonType::CycleCoop => *coop_mode = coop_mode.next(),
+            _ => (),
+        };
+    }
+}
+
+// buttons on the settings pages
+#[allow(clippy::too_many_arguments)]
+pub fn handle_settings_press(
+    mut commands: Commands,
+    mut press_reader: EventReader<ButtonPressEvent>,
+    mut clear_binding_writer: EventWriter<ClearBindingEvent>,
+    mut restore_defaults_writer: EventWriter<RestoreDefaultControllsEvent>,
+    mut conflict_writer: EventWriter<ConflictResolvedEvent>,
+    mut cycle_gamepad_writer: EventWriter<CycleGamepadEvent>,
+    mut touch_controlls: ResMut<TouchControlls>,
+    mut locale: ResMut<Locale>,
+    mut settings_page: ResMut<SettingsPage>,
+    mut accessibility: ResMut<Accessibility>,
+    mut display: ResMut<DisplaySettings>,
+) {
+    for ButtonPressEvent(button_type) in press_reader.read() {
+        match *button_type {
+            ButtonType::SettingsButton(action) => {
+                commands.insert_resource(ControllChange::new(action));
+            }
+            ButtonType::ClearBinding(action) => {
+                clear_binding_writer.send(ClearBindingEvent(action));
+            }
+            ButtonType::RestoreDefaults => {
+                restore_defaults_writer.send(RestoreDefaultControllsEvent);
+            }
+            ButtonType::ResolveConflict(resolution) => {
+                conflict_writer.send(ConflictResolvedEvent(resolution));
+            }
+            ButtonType::CycleGamepad => cycle_gamepad_writer.send(CycleGamepadEvent),
+            ButtonType::ToggleTouchControlls => touch_controlls.hidden = !touch_controlls.hidden,
+            ButtonType::CycleLanguage => {
+                let language = locale.language().next();
+                locale.set_language(language);
+            }
+            ButtonType::SettingsPage(page) => *settings_page = page,
+            ButtonType::CycleTextScale => accessibility.next_text_scale(),
+            ButtonType::CyclePalette => accessibility.palette = accessibility.palette.next(),
+            ButtonType::ToggleReducedMotion => {
+                accessibility.reduced_motion = !accessibility.reduced_motion
+            }
+            ButtonType::ToggleAutoFire => accessibility.auto_fire = !accessibility.auto_fire,
+            ButtonType::CycleDisplayMode => display.mode = display.mode.next(),
+            ButtonType::CycleResolution => display.next_resolution(),
+            ButtonType::ToggleVsync => display.vsync = !display.vsync,
+            ButtonType::CycleFrameCap => display.next_frame_cap(),
+            ButtonType::CycleParticleQuality => {
+                display.particle_quality = display.particle_quality.next()
+            }
+            _ => (),
+        };
+    }
+}
+
+// the focused button looks hovered so keyboard and gamepad players can see where they are
+pub fn update_button_colors(
+    mut query: Query<(Entity, &Interaction, &mut BackgroundColor), With<ButtonType>>,
+    focus: Res<MenuFocus>,
+    theme: Res<Theme>,
+) {
+    for (entity, &interaction, mut background) in &mut query {
+        let color = match interaction {
+            Interaction::Pressed => theme.button_pressed,
+            Interaction::Hovered => theme.button_hover,
+            Interaction::None if focus.0 == Some(entity) => theme.button_hover,
+            Interaction::None => theme.button,
+        };
+
+        if background.0 != color {
+            background.0 = color;
         }
     }
 }
 
-fn handle_background(interaction: Interaction, mut background: Mut<'_, BackgroundColor>) {
-    match interaction {
-        Interaction::Pressed => *background = PRESSED_BUTTON_COLOR.into(),
-        Interaction::Hovered => *background = HOVER_BUTTON_COLOR.into(),
-        Interaction::None => *background = DEFUALT_BUTTON_COLOR.into(),
-    };
+// a button with a fixed label, relabeled whenever the language changes
+pub fn attach_button(
+    parent: &mut ChildBuilder,
+    button_type: ButtonType,
+    message_id: &'static str,
+    locale: &Locale,
+) {
+    spawn_button(
+        parent,
+        button_type,
+        &locale.get(message_id),
+        Some(message_id),
+        150.0,
+    );
+}
+
+// a button whose label is kept up to date by its own system
+pub fn attach_dynamic_button(
+    parent: &mut ChildBuilder,
+    button_type: ButtonType,
+    button_text: &str,
+) {
+    spawn_button(parent, button_type, button_text, None, 150.0);
+}
+
+// a narrow button with a symbol for a label, like the slider steps
+pub fn attach_small_button(parent: &mut ChildBuilder, button_type: ButtonType, symbol: &str) {
+    spawn_button(parent, button_type, symbol, None, 50.0);
 }
 
-pub fn attach_button(parent: &mut ChildBuilder, button_type: ButtonType, button_text: &str) {
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button_type: ButtonType,
+    button_text: &str,
+    message_id: Option<&'static str>,
+    width: f32,
+) {
     let button_style = Style {
-        width: Val::Px(150.0),
+        width: Val::Px(width),
         height: Val::Px(50.0),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
@@ -69,15 +253,15 @@ pub fn attach_button(parent: &mut ChildBuilder, button_type: ButtonType, button_
 
     parent
         .spawn((
+            // painted by update_button_colors before the first frame is drawn
             ButtonBundle {
                 style: button_style,
-                background_color: DEFUALT_BUTTON_COLOR.into(),
                 ..default()
             },
             button_type.clone(),
         ))
         .with_children(|parent| {
-            parent.spawn((
+            let mut text = parent.spawn((
                 TextBundle {
                     text: Text::from_section(button_text, text_style),
                     ..default()
@@ -88,5 +272,9 @@ pub fn attach_button(parent: &mut ChildBuilder, button_type: ButtonType, button_
                     SettingsText(false, None)
                 },
             ));
+
+            if let Some(message_id) = message_id {
+                text.insert(LocalizedText(message_id));
+            }
         });
 }
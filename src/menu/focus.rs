@@ -0,0 +1,303 @@
+use std::{collections::HashSet, hash::Hash};
+
+use bevy::prelude::*;
+
+use super::buttons::{handle_button_press, ButtonPressEvent, ButtonType};
+use crate::game::controlls::{
+    handle_controll_change, ConflictResolution, ControllChange, Controlls, CurrentGamepad,
+};
+
+const FOCUS_MEMORY_LENGTH: usize = 16;
+
+// buttons that act as "back" for the menu they are in, in order of preference
+const BACK_BUTTONS: [ButtonType; 4] = [
+    ButtonType::ResolveConflict(ConflictResolution::Cancel),
+    ButtonType::CloseSettings,
+    ButtonType::Resume,
+    ButtonType::ReturnToMenu,
+];
+
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub Option<Entity>);
+
+// recently pressed buttons, used to put the focus back when returning to a menu
+#[derive(Resource, Default)]
+struct FocusMemory(Vec<ButtonType>);
+
+// overlays keep the focus inside them while they are open
+#[derive(Component)]
+pub struct FocusScope;
+
+// open focus scopes in the order they were opened, the last one has the focus
+#[derive(Resource, Default)]
+struct FocusScopes(Vec<Entity>);
+
+#[derive(Event, Clone, Copy, PartialEq, Eq, Hash)]
+enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+pub struct FocusPlugin;
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .init_resource::<FocusMemory>()
+            .init_resource::<FocusScopes>()
+            .add_event::<MenuInput>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        read_menu_input::<KeyCode>,
+                        read_menu_input::<GamepadButton>,
+                        read_fixed_menu_input,
+                    )
+                        .before(handle_controll_change),
+                    track_focus_scopes,
+                    restore_focus,
+                    navigate_focus,
+                )
+                    .chain()
+                    .before(handle_button_press),
+            )
+            .add_systems(
+                Update,
+                remember_pressed.run_if(on_event::<ButtonPressEvent>()),
+            );
+    }
+}
+
+fn read_menu_input<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    mut menu_input_writer: EventWriter<MenuInput>,
+    input: Res<Input<T>>,
+    controller: Res<Controlls<T>>,
+    controll_change: Option<Res<ControllChange>>,
+) {
+    if controll_change.is_some() {
+        return;
+    }
+
+    let bindings = [
+        (controller.up, MenuInput::Up),
+        (controller.left, MenuInput::Left),
+        (controller.right, MenuInput::Right),
+        (controller.jump, MenuInput::Confirm),
+        (controller.pause, MenuInput::Back),
+    ];
+
+    for (button, menu_input) in bindings {
+        let Some(button) = button else {
+            continue;
+        };
+        if input.just_pressed(button) {
+            menu_input_writer.send(menu_input);
+        }
+    }
+}
+
+// arrows, w and s, enter and the dpad always work in menus, whatever the bindings are
+fn read_fixed_menu_input(
+    mut menu_input_writer: EventWriter<MenuInput>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    current: Res<CurrentGamepad>,
+    controll_change: Option<Res<ControllChange>>,
+) {
+    if controll_change.is_some() {
+        return;
+    }
+
+    let key_bindings = [
+        (KeyCode::Up, MenuInput::Up),
+        (KeyCode::Down, MenuInput::Down),
+        (KeyCode::W, MenuInput::Up),
+        (KeyCode::S, MenuInput::Down),
+        (KeyCode::Left, MenuInput::Left),
+        (KeyCode::Right, MenuInput::Right),
+        (KeyCode::Return, MenuInput::Confirm),
+    ];
+
+    for (key, menu_input) in key_bindings {
+        if keys.just_pressed(key) {
+            menu_input_writer.send(menu_input);
+        }
+    }
+
+    let Some(gamepad) = current.0 else {
+        return;
+    };
+
+    let button_bindings = [
+        (GamepadButtonType::DPadUp, MenuInput::Up),
+        (GamepadButtonType::DPadDown, MenuInput::Down),
+        (GamepadButtonType::DPadLeft, MenuInput::Left),
+        (GamepadButtonType::DPadRight, MenuInput::Right),
+        (GamepadButtonType::South, MenuInput::Confirm),
+        (GamepadButtonType::East, MenuInput::Back),
+    ];
+
+    for (button_type, menu_input) in button_bindings {
+        if buttons.just_pressed(GamepadButton::new(gamepad, button_type)) {
+            menu_input_writer.send(menu_input);
+        }
+    }
+}
+
+fn remember_pressed(
+    mut press_reader: EventReader<ButtonPressEvent>,
+    mut memory: ResMut<FocusMemory>,
+) {
+    for ButtonPressEvent(button_type) in press_reader.read() {
+        memory.0.retain(|remembered| remembered != button_type);
+        memory.0.push(button_type.clone());
+
+        if memory.0.len() > FOCUS_MEMORY_LENGTH {
+            memory.0.remove(0);
+        }
+    }
+}
+
+// entity ids get reused, so the order scopes opened in is kept rather than guessed from them
+fn track_focus_scopes(
+    mut scopes: ResMut<FocusScopes>,
+    mut removed_scopes: RemovedComponents<FocusScope>,
+    added_query: Query<Entity, Added<FocusScope>>,
+) {
+    for removed in removed_scopes.read() {
+        scopes.0.retain(|&scope| scope != removed);
+    }
+    scopes.0.extend(added_query.iter());
+}
+
+// the buttons that can take focus right now, limited to the newest open overlay
+fn focusable_buttons<'a>(
+    button_query: &'a Query<(Entity, &ButtonType, &GlobalTransform)>,
+    scopes: &FocusScopes,
+    parent_query: &Query<&Parent>,
+) -> Vec<(Entity, &'a ButtonType, Vec2)> {
+    let scope = scopes.0.last().copied();
+
+    button_query
+        .iter()
+        .filter(|(entity, _, _)| match scope {
+            Some(scope) => parent_query.iter_ancestors(*entity).any(|e| e == scope),
+            None => true,
+        })
+        .map(|(entity, button_type, transform)| {
+            (entity, button_type, transform.translation().truncate())
+        })
+        .collect()
+}
+
+fn restore_focus(
+    mut focus: ResMut<MenuFocus>,
+    memory: Res<FocusMemory>,
+    button_query: Query<(Entity, &ButtonType, &GlobalTransform)>,
+    added_query: Query<(), Added<ButtonType>>,
+    scopes: Res<FocusScopes>,
+    parent_query: Query<&Parent>,
+) {
+    let buttons = focusable_buttons(&button_query, &scopes, &parent_query);
+    let focus_is_valid = buttons
+        .iter()
+        .any(|(entity, _, _)| Some(*entity) == focus.0);
+
+    if focus_is_valid && added_query.is_empty() {
+        return;
+    }
+
+    // layout has not run for fresh buttons yet, so without a memory
+    // the focus is left empty until the first navigation input
+    focus.0 = memory.0.iter().rev().find_map(|remembered| {
+        buttons
+            .iter()
+            .find(|(_, button_type, _)| *button_type == remembered)
+            .map(|(entity, _, _)| *entity)
+    });
+}
+
+fn navigate_focus(
+    mut menu_input_reader: EventReader<MenuInput>,
+    mut press_writer: EventWriter<ButtonPressEvent>,
+    mut focus: ResMut<MenuFocus>,
+    button_query: Query<(Entity, &ButtonType, &GlobalTransform)>,
+    scopes: Res<FocusScopes>,
+    parent_query: Query<&Parent>,
+) {
+    // several bindings can map to the same menu input, act on each only once
+    let menu_inputs: HashSet<MenuInput> = menu_input_reader.read().copied().collect();
+
+    let buttons = focusable_buttons(&button_query, &scopes, &parent_query);
+    if buttons.is_empty() {
+        return;
+    }
+
+    let focused = buttons
+        .iter()
+        .find(|(entity, _, _)| Some(*entity) == focus.0);
+
+    for menu_input in menu_inputs {
+        let direction = match menu_input {
+            // ui space grows downwards
+            MenuInput::Up => Vec2::NEG_Y,
+            MenuInput::Down => Vec2::Y,
+            MenuInput::Left => Vec2::NEG_X,
+            MenuInput::Right => Vec2::X,
+            MenuInput::Confirm => {
+                if let Some((_, button_type, _)) = focused {
+                    press_writer.send(ButtonPressEvent((*button_type).clone()));
+                } else {
+                    focus.0 = first_button(&buttons);
+                }
+                continue;
+            }
+            MenuInput::Back => {
+                let back_button = BACK_BUTTONS.iter().find(|back| {
+                    buttons
+                        .iter()
+                        .any(|(_, button_type, _)| *button_type == *back)
+                });
+                if let Some(back_button) = back_button {
+                    press_writer.send(ButtonPressEvent(back_button.clone()));
+                }
+                continue;
+            }
+        };
+
+        let Some(&(_, _, position)) = focused else {
+            focus.0 = first_button(&buttons);
+            continue;
+        };
+
+        // prefer buttons straight ahead over closer ones off to the side
+        let next = buttons
+            .iter()
+            .filter_map(|&(entity, _, other)| {
+                let offset = other - position;
+                let ahead = offset.dot(direction);
+                if ahead <= 0.0 {
+                    return None;
+                }
+                let aside = offset.perp_dot(direction).abs();
+                Some((entity, ahead + aside * 2.0))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((entity, _)) = next {
+            focus.0 = Some(entity);
+        }
+    }
+}
+
+// the top left button
+fn first_button(buttons: &[(Entity, &ButtonType, Vec2)]) -> Option<Entity> {
+    buttons
+        .iter()
+        .min_by(|(_, _, a), (_, _, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+        .map(|(entity, _, _)| *entity)
+}
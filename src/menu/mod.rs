@@ -1,17 +1,39 @@
 use std::{fmt::Debug, hash::Hash};
 
 use crate::{
+    accessibility::{Accessibility, Palette, Theme},
+    display::{DisplayMode, DisplaySettings},
     game::{
-        controlls::{CatAction, Controlls, ACTION_LIST},
-        reset_stats, DifficultyMultiplier, Heart, Score, INITIAL_HEART_COUNT,
+        achievements::{UnlockedAchievements, ACHIEVEMENTS},
+        controlls::{
+            CatAction, ConflictResolution, ControllChange, ControllConflict, Controlls,
+            CurrentGamepad, ACTION_LIST,
+        },
+        difficulty::{CustomDifficulty, DifficultyConfig, DifficultyPreset, DIFFICULTY_PARAMS},
+        modes::{record_high_score, GameMode, HighScores, ModeTimer, GAME_MODES},
+        particles::ParticleQuality,
+        stats::{BugSpecies, RunStats, BUG_SPECIES},
+        touch::TouchControlls,
+        weather::WeatherSettings,
+        CoopMode, DifficultyMultiplier, Heart, PlayerScores, Score,
     },
+    locale::{Locale, LocalizedText},
     GameState, SimulationState,
 };
 use bevy::prelude::*;
 
 mod buttons;
+mod focus;
+mod slider;
+pub mod toast;
 
-use buttons::{attach_button, button_interactions, ButtonType};
+use buttons::{
+    attach_button, attach_dynamic_button, button_interactions, handle_button_press,
+    handle_settings_press, update_button_colors, ButtonPressEvent, ButtonType,
+};
+use focus::{FocusPlugin, FocusScope};
+use slider::{attach_difficulty_slider, SliderPlugin};
+use toast::ToastPlugin;
 
 #[derive(Component)]
 struct MainMenu;
@@ -25,6 +47,18 @@ struct GameOverMenu;
 #[derive(Component)]
 struct SettingsMenu;
 
+#[derive(Component)]
+struct ConflictDialog;
+
+#[derive(Component)]
+struct AchievementsMenu;
+
+#[derive(Component)]
+struct ModeSelectMenu;
+
+#[derive(Component)]
+struct ModeText;
+
 #[derive(Component)]
 struct StatsBar;
 
@@ -40,33 +74,129 @@ struct DifficultyText;
 #[derive(Component)]
 struct SettingsText(bool, Option<CatAction>);
 
+// menu backgrounds and stats bar panels, recoloured when the palette changes
+#[derive(Component)]
+struct ThemedBackground;
+
+#[derive(Component)]
+struct ThemedPanel;
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub enum SettingsPage {
+    #[default]
+    Controlls,
+    Accessibility,
+    Display,
+}
+
 pub struct MenusPlugin;
 impl Plugin for MenusPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::MainMenu), (spawn_mainmenu, reset_stats))
+        app.add_plugins((ToastPlugin, FocusPlugin, SliderPlugin))
+            .add_event::<ButtonPressEvent>()
+            .init_resource::<SettingsPage>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_mainmenu)
             .add_systems(OnExit(GameState::MainMenu), despawn_mainmenu)
             .add_systems(OnEnter(GameState::Game), spawn_statsbar)
             .add_systems(OnExit(GameState::Game), despawn_statsbar)
             .add_systems(OnEnter(SimulationState::Paused), spawn_pausemenu)
             .add_systems(OnExit(SimulationState::Paused), despawn_pausemenu)
-            .add_systems(OnEnter(GameState::GameOver), spawn_gameovermenu)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_gameovermenu.after(record_high_score),
+            )
+            .add_systems(OnEnter(GameState::ModeSelect), spawn_mode_select)
+            .add_systems(OnExit(GameState::ModeSelect), despawn_mode_select)
             .add_systems(OnExit(GameState::GameOver), despawn_gameovermenu)
+            .add_systems(OnEnter(GameState::Achievements), spawn_achievements_menu)
+            .add_systems(OnExit(GameState::Achievements), despawn_achievements_menu)
             .add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
-            .add_systems(OnExit(GameState::Settings), despawn_settings_menu)
+            .add_systems(
+                OnExit(GameState::Settings),
+                (despawn_settings_menu, despawn_conflict_dialog),
+            )
+            .add_systems(OnEnter(SimulationState::Settings), spawn_settings_menu)
+            .add_systems(
+                OnExit(SimulationState::Settings),
+                (despawn_settings_menu, despawn_conflict_dialog),
+            )
             .add_systems(
                 Update,
                 (
-                    update_settings_text.run_if(in_state(GameState::Settings)),
-                    button_interactions,
-                    update_score.run_if(resource_changed::<Score>()),
+                    update_settings_text.run_if(settings_open),
+                    update_gamepad_text.run_if(settings_open),
+                    update_touch_text.run_if(settings_open),
+                    update_language_text
+                        .run_if(settings_open)
+                        .run_if(resource_changed::<Locale>()),
+                    update_coop_text.run_if(in_state(GameState::MainMenu)),
+                    update_weather_text.run_if(in_state(GameState::ModeSelect)),
+                    spawn_conflict_dialog::<KeyCode>
+                        .run_if(resource_added::<ControllConflict<KeyCode>>()),
+                    spawn_conflict_dialog::<GamepadButton>
+                        .run_if(resource_added::<ControllConflict<GamepadButton>>()),
+                    despawn_conflict_dialog.run_if(resource_removed::<ControllConflict<KeyCode>>()),
+                    despawn_conflict_dialog
+                        .run_if(resource_removed::<ControllConflict<GamepadButton>>()),
+                    button_interactions.before(handle_button_press),
+                    handle_button_press,
+                    handle_settings_press.after(handle_button_press),
+                    update_score.run_if(
+                        resource_changed::<Score>().or_else(resource_changed::<PlayerScores>()),
+                    ),
                     update_heart.run_if(resource_changed::<Heart>()),
                     update_difficulty.run_if(resource_changed::<DifficultyMultiplier>()),
+                    update_mode_text.run_if(in_state(GameState::Game)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (despawn_settings_menu, spawn_settings_menu)
+                        .chain()
+                        .run_if(resource_changed::<SettingsPage>()),
+                    update_accessibility_text.run_if(
+                        resource_changed::<Accessibility>().or_else(resource_changed::<Locale>()),
+                    ),
+                    update_display_text.run_if(
+                        resource_changed::<DisplaySettings>().or_else(resource_changed::<Locale>()),
+                    ),
+                )
+                    .after(handle_settings_press)
+                    .run_if(settings_open),
+            )
+            .add_systems(
+                Update,
+                (despawn_mode_select, spawn_mode_select)
+                    .chain()
+                    .after(handle_button_press)
+                    .run_if(in_state(GameState::ModeSelect))
+                    .run_if(resource_changed::<DifficultyPreset>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    update_button_colors,
+                    recolor_themed_nodes.run_if(resource_changed::<Theme>()),
                 ),
             );
     }
 }
 
-fn spawn_mainmenu(mut commands: Commands) {
+// settings are either their own screen from the main menu or an overlay on the pause menu
+fn settings_open(
+    game_state: Res<State<GameState>>,
+    sim_state: Res<State<SimulationState>>,
+) -> bool {
+    *game_state.get() == GameState::Settings || *sim_state.get() == SimulationState::Settings
+}
+
+fn spawn_mainmenu(
+    mut commands: Commands,
+    coop_mode: Res<CoopMode>,
+    locale: Res<Locale>,
+    theme: Res<Theme>,
+) {
     let menu_style = Style {
         flex_direction: FlexDirection::Column,
         align_items: AlignItems::Center,
@@ -80,18 +210,26 @@ fn spawn_mainmenu(mut commands: Commands) {
 
     let base = NodeBundle {
         style: menu_style,
-        background_color: Color::rgb(0.988, 0.875, 0.804).into(),
+        background_color: theme.background.into(),
         ..default()
     };
 
-    commands.spawn((base, MainMenu)).with_children(|parent| {
-        attach_button(parent, ButtonType::Play, "Play");
-        attach_button(parent, ButtonType::Settings, "Settings");
-        attach_button(parent, ButtonType::Quit, "Quit");
-    });
+    commands
+        .spawn((base, MainMenu, ThemedBackground))
+        .with_children(|parent| {
+            attach_button(parent, ButtonType::Play, "play", &locale);
+            attach_dynamic_button(
+                parent,
+                ButtonType::CycleCoop,
+                &coop_text(*coop_mode, &locale),
+            );
+            attach_button(parent, ButtonType::Settings, "settings", &locale);
+            attach_button(parent, ButtonType::Achievements, "achievements", &locale);
+            attach_button(parent, ButtonType::Quit, "quit", &locale);
+        });
 }
 
-fn spawn_pausemenu(mut commands: Commands) {
+fn spawn_pausemenu(mut commands: Commands, locale: Res<Locale>) {
     let menu_style = Style {
         flex_direction: FlexDirection::Column,
         align_items: AlignItems::Center,
@@ -110,13 +248,21 @@ fn spawn_pausemenu(mut commands: Commands) {
     };
 
     commands.spawn((base, PauseMenu)).with_children(|parent| {
-        attach_button(parent, ButtonType::Resume, "Continue");
-        attach_button(parent, ButtonType::ReturnToMenu, "Main Menu");
-        attach_button(parent, ButtonType::Quit, "Quit");
+        attach_button(parent, ButtonType::Resume, "continue", &locale);
+        attach_button(parent, ButtonType::Restart, "restart", &locale);
+        attach_button(parent, ButtonType::Settings, "settings", &locale);
+        attach_button(parent, ButtonType::ReturnToMenu, "main-menu", &locale);
+        attach_button(parent, ButtonType::Quit, "quit", &locale);
     });
 }
 
-fn spawn_statsbar(mut commands: Commands) {
+fn spawn_statsbar(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    theme: Res<Theme>,
+    mode: Res<GameMode>,
+    config: Res<DifficultyConfig>,
+) {
     let bar_style = Style {
         flex_direction: FlexDirection::Row,
         align_items: AlignItems::Start,
@@ -155,7 +301,7 @@ fn spawn_statsbar(mut commands: Commands) {
 
     let score_text = TextBundle {
         text: Text::from_sections([
-            TextSection::new("score: ", text_style.clone()),
+            TextSection::new(locale.get("score"), text_style.clone()),
             TextSection::new("0", text_style.clone()),
         ]),
         ..default()
@@ -163,16 +309,27 @@ fn spawn_statsbar(mut commands: Commands) {
 
     let heart_text = TextBundle {
         text: Text::from_sections([
-            TextSection::new("Hearts: ", text_style.clone()),
-            TextSection::new(INITIAL_HEART_COUNT.to_string(), text_style.clone()),
+            TextSection::new(locale.get("hearts"), text_style.clone()),
+            TextSection::new(mode.initial_hearts(&config).to_string(), text_style.clone()),
         ]),
         ..default()
     };
 
     let difficulty_text = TextBundle {
         text: Text::from_sections([
-            TextSection::new("Difficulty: ", text_style.clone()),
-            TextSection::new("1x", text_style.clone()),
+            TextSection::new(locale.get("difficulty"), text_style.clone()),
+            TextSection::new(
+                format!("{:.2}x", mode.initial_difficulty(&config)),
+                text_style.clone(),
+            ),
+        ]),
+        ..default()
+    };
+
+    let mode_text = TextBundle {
+        text: Text::from_sections([
+            TextSection::new(locale.get(mode_message_id(*mode)), text_style.clone()),
+            TextSection::new("", text_style.clone()),
         ]),
         ..default()
     };
@@ -188,36 +345,67 @@ fn spawn_statsbar(mut commands: Commands) {
         ))
         .with_children(|parent| {
             parent
-                .spawn(NodeBundle {
-                    style: box_style.clone(),
-                    background_color: Color::hsl(0.0, 0.1, 0.3).into(),
-                    ..default()
-                })
+                .spawn((
+                    NodeBundle {
+                        style: box_style.clone(),
+                        background_color: theme.panel.into(),
+                        ..default()
+                    },
+                    ThemedPanel,
+                ))
                 .with_children(|parent| {
-                    parent.spawn((score_text, ScoreText));
+                    parent.spawn((score_text, ScoreText, LocalizedText("score")));
                 });
             parent
-                .spawn(NodeBundle {
-                    style: box_style.clone(),
-                    background_color: Color::hsl(0.0, 0.1, 0.3).into(),
-                    ..default()
-                })
+                .spawn((
+                    NodeBundle {
+                        style: box_style.clone(),
+                        background_color: theme.panel.into(),
+                        ..default()
+                    },
+                    ThemedPanel,
+                ))
                 .with_children(|parent| {
-                    parent.spawn((heart_text, HeartText));
+                    parent.spawn((heart_text, HeartText, LocalizedText("hearts")));
                 });
             parent
-                .spawn(NodeBundle {
-                    style: box_style.clone(),
-                    background_color: Color::hsl(0.0, 0.1, 0.3).into(),
-                    ..default()
-                })
+                .spawn((
+                    NodeBundle {
+                        style: box_style.clone(),
+                        background_color: theme.panel.into(),
+                        ..default()
+                    },
+                    ThemedPanel,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((mode_text, ModeText, LocalizedText(mode_message_id(*mode))));
+                });
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: box_style.clone(),
+                        background_color: theme.panel.into(),
+                        ..default()
+                    },
+                    ThemedPanel,
+                ))
                 .with_children(|parent| {
-                    parent.spawn((difficulty_text, DifficultyText));
+                    parent.spawn((difficulty_text, DifficultyText, LocalizedText("difficulty")));
                 });
         });
 }
 
-fn spawn_gameovermenu(mut commands: Commands, score: Res<Score>) {
+#[allow(clippy::too_many_arguments)]
+fn spawn_gameovermenu(
+    mut commands: Commands,
+    score: Res<Score>,
+    player_scores: Res<PlayerScores>,
+    coop_mode: Res<CoopMode>,
+    run_stats: Res<RunStats>,
+    mode: Res<GameMode>,
+    high_scores: Res<HighScores>,
+    locale: Res<Locale>,
+) {
     let menu_style = Style {
         flex_direction: FlexDirection::Column,
         align_items: AlignItems::Center,
@@ -229,12 +417,24 @@ fn spawn_gameovermenu(mut commands: Commands, score: Res<Score>) {
         ..default()
     };
 
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
     let text_style = TextStyle {
         font_size: 20.0,
         color: Color::WHITE,
         ..default()
     };
 
+    let summary_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
     let base = NodeBundle {
         style: menu_style,
         background_color: Color::rgba(0.988, 0.875, 0.804, 0.0).into(),
@@ -245,16 +445,360 @@ fn spawn_gameovermenu(mut commands: Commands, score: Res<Score>) {
         .spawn((base, GameOverMenu))
         .with_children(|parent| {
             parent.spawn(TextBundle {
-                text: Text::from_section(format!("Score: {}", score.0), text_style),
+                text: Text::from_section(
+                    locale.format("final-score", &[&score.0]),
+                    text_style.clone(),
+                ),
+                ..default()
+            });
+            if *coop_mode != CoopMode::Solo {
+                parent.spawn(TextBundle {
+                    text: Text::from_section(
+                        locale.format(
+                            "final-coop-score",
+                            &[&player_scores.0[0], &player_scores.0[1]],
+                        ),
+                        text_style,
+                    ),
+                    ..default()
+                });
+            }
+            // the score was already recorded, so a new best is the top of the table
+            if score.0 > 0 && high_scores.best(*mode) == Some(score.0) {
+                parent.spawn(TextBundle {
+                    text: Text::from_section(locale.get("new-best"), summary_style.clone()),
+                    ..default()
+                });
+            }
+            let table: Vec<String> = high_scores
+                .table(*mode)
+                .iter()
+                .map(u32::to_string)
+                .collect();
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    locale.format(
+                        "high-scores",
+                        &[&locale.get(mode_message_id(*mode)), &table.join(", ")],
+                    ),
+                    summary_style.clone(),
+                ),
+                ..default()
+            });
+            for line in run_summary(&run_stats, &locale) {
+                parent.spawn(TextBundle {
+                    text: Text::from_section(line, summary_style.clone()),
+                    ..default()
+                });
+            }
+            parent
+                .spawn(NodeBundle {
+                    style: row_style,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    attach_button(parent, ButtonType::Restart, "retry", &locale);
+                    attach_button(parent, ButtonType::ExportStats, "export-stats", &locale);
+                    attach_button(parent, ButtonType::ReturnToMenu, "main-menu", &locale);
+                    attach_button(parent, ButtonType::Quit, "quit", &locale);
+                });
+        });
+}
+
+fn mode_message_id(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Endless => "mode-endless",
+        GameMode::TimeAttack => "mode-time-attack",
+        GameMode::Pacifist => "mode-pacifist",
+        GameMode::Hardcore => "mode-hardcore",
+    }
+}
+
+fn mode_description_id(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::Endless => "mode-endless-desc",
+        GameMode::TimeAttack => "mode-time-attack-desc",
+        GameMode::Pacifist => "mode-pacifist-desc",
+        GameMode::Hardcore => "mode-hardcore-desc",
+    }
+}
+
+fn preset_text(preset: DifficultyPreset, locale: &Locale) -> String {
+    let preset_name = locale.get(match preset {
+        DifficultyPreset::Easy => "preset-easy",
+        DifficultyPreset::Normal => "preset-normal",
+        DifficultyPreset::Hard => "preset-hard",
+        DifficultyPreset::Custom => "preset-custom",
+    });
+    locale.format("difficulty-preset", &[&preset_name])
+}
+
+fn spawn_mode_select(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    weather_settings: Res<WeatherSettings>,
+    preset: Res<DifficultyPreset>,
+    custom: Res<CustomDifficulty>,
+    locale: Res<Locale>,
+    theme: Res<Theme>,
+) {
+    let menu_style = Style {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        row_gap: Val::Px(10.0),
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(10.0),
+        width: Val::Px(500.0),
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_style,
+                background_color: theme.background.into(),
+                ..default()
+            },
+            ModeSelectMenu,
+            ThemedBackground,
+        ))
+        .with_children(|parent| {
+            for mode in GAME_MODES {
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: row_style.clone(),
+                            background_color: theme.panel.into(),
+                            ..default()
+                        },
+                        ThemedPanel,
+                    ))
+                    .with_children(|parent| {
+                        attach_button(
+                            parent,
+                            ButtonType::SelectMode(mode),
+                            mode_message_id(mode),
+                            &locale,
+                        );
+
+                        let best = high_scores
+                            .best(mode)
+                            .map(|best| best.to_string())
+                            .unwrap_or_else(|| locale.get("none"));
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                format!(
+                                    "{}\n{}",
+                                    locale.get(mode_description_id(mode)),
+                                    locale.format("best", &[&best]),
+                                ),
+                                text_style.clone(),
+                            ),
+                            ..default()
+                        });
+
+                        attach_dynamic_button(
+                            parent,
+                            ButtonType::ToggleWeather(mode),
+                            &weather_text(weather_settings.enabled(mode), &locale),
+                        );
+                    });
+            }
+
+            attach_dynamic_button(
+                parent,
+                ButtonType::CycleDifficultyPreset,
+                &preset_text(*preset, &locale),
+            );
+            // the sliders only show for the custom preset, changing the preset respawns the menu
+            if *preset == DifficultyPreset::Custom {
+                for param in DIFFICULTY_PARAMS {
+                    attach_difficulty_slider(parent, param, &custom.0, &locale, &theme);
+                }
+            }
+
+            attach_button(parent, ButtonType::ReturnToMenu, "return", &locale);
+        });
+}
+
+fn species_name(species: BugSpecies, locale: &Locale) -> String {
+    locale.get(match species {
+        BugSpecies::OrangeFly => "bug-orange-fly",
+        BugSpecies::BlueFly => "bug-blue-fly",
+        BugSpecies::Crawler => "bug-crawler",
+        BugSpecies::Firefly => "bug-firefly",
+        BugSpecies::Moth => "bug-moth",
+    })
+}
+
+// one line per statistic for the game over screen
+fn run_summary(run_stats: &RunStats, locale: &Locale) -> Vec<String> {
+    let run_time = run_stats.run_time as u32;
+    let mut lines = vec![locale.format(
+        "stat-time",
+        &[&format!("{}:{:02}", run_time / 60, run_time % 60)],
+    )];
+
+    for species in BUG_SPECIES {
+        lines.push(locale.format(
+            "stat-species",
+            &[
+                &species_name(species, locale),
+                &run_stats.kills(species),
+                &run_stats.escapes(species),
+            ],
+        ));
+    }
+
+    lines.push(locale.format(
+        "stat-shots",
+        &[
+            &run_stats.shots_fired,
+            &((run_stats.accuracy() * 100.0).round() as u32),
+        ],
+    ));
+    lines.push(locale.format("stat-blocks", &[&run_stats.blocks_placed]));
+    lines.push(locale.format("stat-jumps", &[&run_stats.jumps]));
+    lines.push(locale.format(
+        "stat-peak-difficulty",
+        &[&format!("{:.2}", run_stats.peak_difficulty)],
+    ));
+    lines
+}
+
+fn spawn_achievements_menu(
+    mut commands: Commands,
+    unlocked: Res<UnlockedAchievements>,
+    locale: Res<Locale>,
+    theme: Res<Theme>,
+) {
+    let menu_style = Style {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        row_gap: Val::Px(10.0),
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    let entry_style = Style {
+        flex_direction: FlexDirection::Column,
+        width: Val::Px(400.0),
+        padding: UiRect::all(Val::Px(5.0)),
+        ..default()
+    };
+
+    let title_style = TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    let name_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    let description_style = TextStyle {
+        font_size: 12.0,
+        color: Color::rgb(0.8, 0.8, 0.8),
+        ..default()
+    };
+
+    let unlocked_count = ACHIEVEMENTS
+        .iter()
+        .filter(|achievement| unlocked.0.contains(achievement.id))
+        .count();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: menu_style,
+                background_color: theme.background.into(),
+                ..default()
+            },
+            AchievementsMenu,
+            ThemedBackground,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    locale.format(
+                        "achievements-unlocked",
+                        &[&unlocked_count, &ACHIEVEMENTS.len()],
+                    ),
+                    title_style,
+                ),
                 ..default()
             });
-            attach_button(parent, ButtonType::ReturnToMenu, "Main Menu");
-            attach_button(parent, ButtonType::Quit, "Quit");
+
+            for achievement in &ACHIEVEMENTS {
+                // locked ones are drawn dimmer, the mark keeps them apart without colour
+                let (mark, alpha) = if unlocked.0.contains(achievement.id) {
+                    ("[x]", 1.0)
+                } else {
+                    ("[ ]", 0.5)
+                };
+
+                parent
+                    .spawn(NodeBundle {
+                        style: entry_style.clone(),
+                        background_color: theme.panel.with_a(alpha).into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                format!("{} {}", mark, locale.get(achievement.name)),
+                                name_style.clone(),
+                            ),
+                            ..default()
+                        });
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                locale.get(achievement.description),
+                                description_style.clone(),
+                            ),
+                            ..default()
+                        });
+                    });
+            }
+
+            attach_button(parent, ButtonType::ReturnToMenu, "return", &locale);
         });
 }
 
-fn spawn_settings_menu(mut commands: Commands, kbd_controlls: Res<Controlls<KeyCode>>) {
+#[allow(clippy::too_many_arguments)]
+fn spawn_settings_menu(
+    mut commands: Commands,
+    kbd_controlls: Res<Controlls<KeyCode>>,
+    touch_controlls: Res<TouchControlls>,
+    accessibility: Res<Accessibility>,
+    display: Res<DisplaySettings>,
+    settings_page: Res<SettingsPage>,
+    locale: Res<Locale>,
+    theme: Res<Theme>,
+) {
     let menu_style = Style {
+        flex_direction: FlexDirection::Column,
         align_items: AlignItems::Center,
         justify_content: JustifyContent::Center,
         width: Val::Percent(100.0),
@@ -264,94 +808,222 @@ fn spawn_settings_menu(mut commands: Commands, kbd_controlls: Res<Controlls<KeyC
         ..default()
     };
 
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
     commands
         .spawn((
             NodeBundle {
                 style: menu_style,
-                background_color: Color::rgb(0.988, 0.875, 0.804).into(),
+                background_color: theme.background.into(),
+                z_index: ZIndex::Global(1),
                 ..default()
             },
             SettingsMenu,
+            ThemedBackground,
         ))
         .with_children(|parent| {
-            attach_button(parent, ButtonType::ReturnToMenu, "Return");
-            for action in ACTION_LIST {
-                attach_button(
-                    parent,
-                    ButtonType::SettingsButton(action),
-                    &get_action_text(action, &kbd_controlls),
-                );
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    attach_button(
+                        parent,
+                        ButtonType::SettingsPage(SettingsPage::Controlls),
+                        "tab-controls",
+                        &locale,
+                    );
+                    attach_button(
+                        parent,
+                        ButtonType::SettingsPage(SettingsPage::Accessibility),
+                        "tab-accessibility",
+                        &locale,
+                    );
+                    attach_button(
+                        parent,
+                        ButtonType::SettingsPage(SettingsPage::Display),
+                        "tab-display",
+                        &locale,
+                    );
+                });
+
+            match *settings_page {
+                SettingsPage::Controlls => {
+                    for action in ACTION_LIST {
+                        parent
+                            .spawn(NodeBundle {
+                                style: row_style.clone(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                attach_dynamic_button(
+                                    parent,
+                                    ButtonType::SettingsButton(action),
+                                    &get_action_text(action, &kbd_controlls, &locale),
+                                );
+                                attach_button(
+                                    parent,
+                                    ButtonType::ClearBinding(action),
+                                    "clear",
+                                    &locale,
+                                );
+                            });
+                    }
+                }
+                SettingsPage::Accessibility => {
+                    for button_type in ACCESSIBILITY_BUTTONS {
+                        attach_dynamic_button(
+                            parent,
+                            button_type.clone(),
+                            &accessibility_text(&button_type, &accessibility, &locale),
+                        );
+                    }
+                }
+                SettingsPage::Display => {
+                    for button_type in DISPLAY_BUTTONS {
+                        attach_dynamic_button(
+                            parent,
+                            button_type.clone(),
+                            &display_text(&button_type, &display, &locale),
+                        );
+                    }
+                }
             }
+
+            parent
+                .spawn(NodeBundle {
+                    style: row_style.clone(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    attach_button(parent, ButtonType::CloseSettings, "return", &locale);
+                    if *settings_page == SettingsPage::Controlls {
+                        attach_button(
+                            parent,
+                            ButtonType::RestoreDefaults,
+                            "restore-defaults",
+                            &locale,
+                        );
+                        attach_dynamic_button(parent, ButtonType::CycleGamepad, "");
+                        attach_dynamic_button(
+                            parent,
+                            ButtonType::ToggleTouchControlls,
+                            &touch_text(&touch_controlls, &locale),
+                        );
+                    }
+                    attach_dynamic_button(
+                        parent,
+                        ButtonType::CycleLanguage,
+                        &language_text(&locale),
+                    );
+                });
+        });
+}
+
+fn spawn_conflict_dialog<T: Send + Sync + 'static>(
+    mut commands: Commands,
+    conflict: Res<ControllConflict<T>>,
+    locale: Res<Locale>,
+) {
+    let dialog_style = Style {
+        position_type: PositionType::Absolute,
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        row_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: dialog_style,
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(2),
+                ..default()
+            },
+            ConflictDialog,
+            FocusScope,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    locale.format(
+                        "conflict",
+                        &[
+                            &action_name(conflict.conflicting, &locale),
+                            &action_name(conflict.action, &locale),
+                        ],
+                    ),
+                    text_style,
+                ),
+                ..default()
+            });
+            attach_button(
+                parent,
+                ButtonType::ResolveConflict(ConflictResolution::Swap),
+                "swap",
+                &locale,
+            );
+            attach_button(
+                parent,
+                ButtonType::ResolveConflict(ConflictResolution::Replace),
+                "replace",
+                &locale,
+            );
+            attach_button(
+                parent,
+                ButtonType::ResolveConflict(ConflictResolution::Cancel),
+                "cancel",
+                &locale,
+            );
         });
 }
 
+fn action_name(action: CatAction, locale: &Locale) -> String {
+    locale.get(match action {
+        CatAction::Up => "action-up",
+        CatAction::Left => "action-left",
+        CatAction::Right => "action-right",
+        CatAction::Jump => "action-jump",
+        CatAction::Fire => "action-fire",
+        CatAction::ToggleWeapon => "action-toggle-weapon",
+        CatAction::PlaceBlock => "action-place-block",
+        CatAction::Pause => "action-pause",
+        CatAction::Restart => "action-restart",
+    })
+}
+
 fn get_action_text<T: Debug + Copy + Eq + Hash + Send + Sync + 'static>(
     action: CatAction,
-    controlls: &Res<Controlls<T>>,
+    controlls: &Controlls<T>,
+    locale: &Locale,
 ) -> String {
-    match action {
-        CatAction::Up => {
-            if let Some(button) = controlls.up {
-                format!("Up: {:?}", button)
-            } else {
-                "Up: None".to_string()
-            }
-        }
-        CatAction::Left => {
-            if let Some(button) = controlls.left {
-                format!("Left: {:?}", button)
-            } else {
-                "Left: None".to_string()
-            }
-        }
-        CatAction::Right => {
-            if let Some(button) = controlls.right {
-                format!("Right: {:?}", button)
-            } else {
-                "Right: None".to_string()
-            }
-        }
-        CatAction::Jump => {
-            if let Some(button) = controlls.jump {
-                format!("Jump: {:?}", button)
-            } else {
-                "Jump: None".to_string()
-            }
-        }
-        CatAction::Fire => {
-            if let Some(button) = controlls.fire {
-                format!("Fire: {:?}", button)
-            } else {
-                "Fire: None".to_string()
-            }
-        }
-        CatAction::ToggleWeapon => {
-            if let Some(button) = controlls.toggle_weapon {
-                format!("ToggleWeapon: {:?}", button)
-            } else {
-                "ToggleWeapon: None".to_string()
-            }
-        }
-        CatAction::PlaceBlock => {
-            if let Some(button) = controlls.place_block {
-                format!("PlaceBlock: {:?}", button)
-            } else {
-                "PlaceBlock: None".to_string()
-            }
-        }
-        CatAction::Pause => {
-            if let Some(button) = controlls.pause {
-                format!("Pause: {:?}", button)
-            } else {
-                "Pause: None".to_string()
-            }
-        }
+    if let Some(button) = controlls.get(action) {
+        format!("{}: {:?}", action_name(action, locale), button)
+    } else {
+        format!("{}: {}", action_name(action, locale), locale.get("none"))
     }
 }
 
 fn update_settings_text(
     mut query: Query<(&mut Text, &SettingsText)>,
     kbd_controlls: Res<Controlls<KeyCode>>,
+    controll_change: Option<Res<ControllChange>>,
+    locale: Res<Locale>,
 ) {
     for (mut text, settings_text) in &mut query {
         if !settings_text.0 {
@@ -362,15 +1034,281 @@ fn update_settings_text(
             continue;
         };
 
-        text.sections[0].value = get_action_text(action, &kbd_controlls);
+        text.sections[0].value = match controll_change {
+            Some(ref change) if change.0 == action => {
+                locale.format("press-key", &[&change.1.remaining_secs().ceil()])
+            }
+            _ => get_action_text(action, &kbd_controlls, &locale),
+        };
+    }
+}
+
+fn coop_text(coop_mode: CoopMode, locale: &Locale) -> String {
+    locale.get(match coop_mode {
+        CoopMode::Solo => "coop-off",
+        CoopMode::SplitKeyboard => "coop-keyboard",
+        CoopMode::KeyboardAndGamepad => "coop-gamepad",
+    })
+}
+
+fn weather_text(enabled: bool, locale: &Locale) -> String {
+    locale.format("weather", &[&on_off_text(enabled, locale)])
+}
+
+fn update_weather_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    weather_settings: Res<WeatherSettings>,
+    locale: Res<Locale>,
+) {
+    if !weather_settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    for mode in GAME_MODES {
+        set_button_text(
+            &button_query,
+            &mut text_query,
+            ButtonType::ToggleWeather(mode),
+            weather_text(weather_settings.enabled(mode), &locale),
+        );
+    }
+}
+
+fn set_button_text(
+    button_query: &Query<(&ButtonType, &Children)>,
+    text_query: &mut Query<&mut Text>,
+    target: ButtonType,
+    value: String,
+) {
+    for (button_type, children) in button_query {
+        if *button_type != target {
+            continue;
+        }
+
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = value.clone();
+            }
+        }
+    }
+}
+
+fn update_coop_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    coop_mode: Res<CoopMode>,
+    locale: Res<Locale>,
+) {
+    if !coop_mode.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    set_button_text(
+        &button_query,
+        &mut text_query,
+        ButtonType::CycleCoop,
+        coop_text(*coop_mode, &locale),
+    );
+}
+
+fn update_gamepad_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    current: Res<CurrentGamepad>,
+    gamepads: Res<Gamepads>,
+    locale: Res<Locale>,
+) {
+    let gamepad_name = current
+        .0
+        .and_then(|gamepad| gamepads.name(gamepad))
+        .map(str::to_string)
+        .unwrap_or_else(|| locale.get("none"));
+
+    set_button_text(
+        &button_query,
+        &mut text_query,
+        ButtonType::CycleGamepad,
+        locale.format("gamepad", &[&gamepad_name]),
+    );
+}
+
+fn touch_text(touch_controlls: &TouchControlls, locale: &Locale) -> String {
+    if touch_controlls.hidden {
+        locale.get("touch-hidden")
+    } else {
+        locale.get("touch-auto")
+    }
+}
+
+fn update_touch_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    touch_controlls: Res<TouchControlls>,
+    locale: Res<Locale>,
+) {
+    if !touch_controlls.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    set_button_text(
+        &button_query,
+        &mut text_query,
+        ButtonType::ToggleTouchControlls,
+        touch_text(&touch_controlls, &locale),
+    );
+}
+
+fn language_text(locale: &Locale) -> String {
+    locale.format("language", &[&locale.language().name()])
+}
+
+fn update_language_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    locale: Res<Locale>,
+) {
+    set_button_text(
+        &button_query,
+        &mut text_query,
+        ButtonType::CycleLanguage,
+        language_text(&locale),
+    );
+}
+
+const ACCESSIBILITY_BUTTONS: [ButtonType; 4] = [
+    ButtonType::CycleTextScale,
+    ButtonType::CyclePalette,
+    ButtonType::ToggleReducedMotion,
+    ButtonType::ToggleAutoFire,
+];
+
+fn on_off_text(value: bool, locale: &Locale) -> String {
+    locale.get(if value { "on" } else { "off" })
+}
+
+fn accessibility_text(
+    button_type: &ButtonType,
+    accessibility: &Accessibility,
+    locale: &Locale,
+) -> String {
+    match button_type {
+        ButtonType::CycleTextScale => locale.format(
+            "text-size",
+            &[&((accessibility.text_scale * 100.0).round() as u32)],
+        ),
+        ButtonType::CyclePalette => locale.get(match accessibility.palette {
+            Palette::Default => "palette-default",
+            Palette::HighContrast => "palette-high-contrast",
+            Palette::ColourBlind => "palette-colour-blind",
+        }),
+        ButtonType::ToggleReducedMotion => locale.format(
+            "reduced-motion",
+            &[&on_off_text(accessibility.reduced_motion, locale)],
+        ),
+        ButtonType::ToggleAutoFire => locale.format(
+            "auto-fire",
+            &[&on_off_text(accessibility.auto_fire, locale)],
+        ),
+        _ => String::new(),
+    }
+}
+
+fn update_accessibility_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    accessibility: Res<Accessibility>,
+    locale: Res<Locale>,
+) {
+    for button_type in ACCESSIBILITY_BUTTONS {
+        let value = accessibility_text(&button_type, &accessibility, &locale);
+        set_button_text(&button_query, &mut text_query, button_type, value);
+    }
+}
+
+const DISPLAY_BUTTONS: [ButtonType; 5] = [
+    ButtonType::CycleDisplayMode,
+    ButtonType::CycleResolution,
+    ButtonType::ToggleVsync,
+    ButtonType::CycleFrameCap,
+    ButtonType::CycleParticleQuality,
+];
+
+fn display_text(button_type: &ButtonType, display: &DisplaySettings, locale: &Locale) -> String {
+    match button_type {
+        ButtonType::CycleDisplayMode => locale.get(match display.mode {
+            DisplayMode::Windowed => "display-windowed",
+            DisplayMode::Borderless => "display-borderless",
+            DisplayMode::Fullscreen => "display-fullscreen",
+        }),
+        ButtonType::CycleResolution => locale.format(
+            "resolution",
+            &[&format!(
+                "{}x{}",
+                display.resolution.0, display.resolution.1
+            )],
+        ),
+        ButtonType::ToggleVsync => locale.format("vsync", &[&on_off_text(display.vsync, locale)]),
+        ButtonType::CycleFrameCap => match display.frame_cap {
+            Some(cap) => locale.format("frame-cap", &[&cap]),
+            None => locale.format("frame-cap", &[&locale.get("unlimited")]),
+        },
+        ButtonType::CycleParticleQuality => locale.get(match display.particle_quality {
+            ParticleQuality::Off => "particles-off",
+            ParticleQuality::Low => "particles-low",
+            ParticleQuality::Medium => "particles-medium",
+            ParticleQuality::High => "particles-high",
+        }),
+        _ => String::new(),
     }
 }
 
-fn update_score(mut query: Query<&mut Text, With<ScoreText>>, score: Res<Score>) {
+fn update_display_text(
+    button_query: Query<(&ButtonType, &Children)>,
+    mut text_query: Query<&mut Text>,
+    display: Res<DisplaySettings>,
+    locale: Res<Locale>,
+) {
+    for button_type in DISPLAY_BUTTONS {
+        let value = display_text(&button_type, &display, &locale);
+        set_button_text(&button_query, &mut text_query, button_type, value);
+    }
+}
+
+fn recolor_themed_nodes(
+    mut background_query: Query<
+        &mut BackgroundColor,
+        (With<ThemedBackground>, Without<ThemedPanel>),
+    >,
+    mut panel_query: Query<&mut BackgroundColor, With<ThemedPanel>>,
+    theme: Res<Theme>,
+) {
+    for mut background in &mut background_query {
+        background.0 = theme.background;
+    }
+    for mut panel in &mut panel_query {
+        panel.0 = theme.panel;
+    }
+}
+
+fn update_score(
+    mut query: Query<&mut Text, With<ScoreText>>,
+    score: Res<Score>,
+    player_scores: Res<PlayerScores>,
+    coop_mode: Res<CoopMode>,
+    locale: Res<Locale>,
+) {
     let Ok(mut score_text) = query.get_single_mut() else {
         return;
     };
-    score_text.sections[1].value = score.0.to_string();
+    score_text.sections[1].value = if *coop_mode == CoopMode::Solo {
+        score.0.to_string()
+    } else {
+        locale.format(
+            "coop-score",
+            &[&score.0, &player_scores.0[0], &player_scores.0[1]],
+        )
+    };
 }
 
 fn update_heart(mut query: Query<&mut Text, With<HeartText>>, heart: Res<Heart>) {
@@ -380,6 +1318,23 @@ fn update_heart(mut query: Query<&mut Text, With<HeartText>>, heart: Res<Heart>)
     heart_text.sections[1].value = heart.0.to_string();
 }
 
+// time attack shows the time left next to the mode name
+fn update_mode_text(
+    mut query: Query<&mut Text, With<ModeText>>,
+    mode: Res<GameMode>,
+    mode_timer: Res<ModeTimer>,
+) {
+    if *mode != GameMode::TimeAttack {
+        return;
+    }
+    let Ok(mut mode_text) = query.get_single_mut() else {
+        return;
+    };
+
+    let remaining = mode_timer.0.remaining_secs().ceil() as u32;
+    mode_text.sections[1].value = format!(" {}:{:02}", remaining / 60, remaining % 60);
+}
+
 fn update_difficulty(
     mut query: Query<&mut Text, With<DifficultyText>>,
     diff: Res<DifficultyMultiplier>,
@@ -411,6 +1366,20 @@ fn despawn_gameovermenu(mut commands: Commands, query: Query<Entity, With<GameOv
     commands.entity(entity).despawn_recursive();
 }
 
+fn despawn_achievements_menu(mut commands: Commands, query: Query<Entity, With<AchievementsMenu>>) {
+    let Ok(entity) = query.get_single() else {
+        return;
+    };
+    commands.entity(entity).despawn_recursive();
+}
+
+fn despawn_mode_select(mut commands: Commands, query: Query<Entity, With<ModeSelectMenu>>) {
+    let Ok(entity) = query.get_single() else {
+        return;
+    };
+    commands.entity(entity).despawn_recursive();
+}
+
 fn despawn_settings_menu(mut commands: Commands, query: Query<Entity, With<SettingsMenu>>) {
     let Ok(entity) = query.get_single() else {
         return;
@@ -418,6 +1387,12 @@ fn despawn_settings_menu(mut commands: Commands, query: Query<Entity, With<Setti
     commands.entity(entity).despawn_recursive();
 }
 
+fn despawn_conflict_dialog(mut commands: Commands, query: Query<Entity, With<ConflictDialog>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn despawn_statsbar(mut commands: Commands, query: Query<Entity, With<StatsBar>>) {
     let Ok(entity) = query.get_single() else {
         return;
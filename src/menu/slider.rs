@@ -0,0 +1,180 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use super::buttons::{attach_small_button, ButtonType};
+use crate::{
+    accessibility::Theme,
+    game::difficulty::{CustomDifficulty, DifficultyConfig, DifficultyParam},
+    locale::{Locale, LocalizedText},
+    GameState,
+};
+
+// the track of a custom difficulty slider, dragged with the mouse
+#[derive(Component)]
+struct DifficultySlider(DifficultyParam);
+
+#[derive(Component)]
+struct SliderFill(DifficultyParam);
+
+#[derive(Component)]
+struct SliderValue(DifficultyParam);
+
+pub struct SliderPlugin;
+impl Plugin for SliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                drag_difficulty_sliders,
+                update_difficulty_sliders
+                    .after(drag_difficulty_sliders)
+                    .run_if(resource_changed::<CustomDifficulty>()),
+            )
+                .run_if(in_state(GameState::ModeSelect)),
+        );
+    }
+}
+
+fn param_message_id(param: DifficultyParam) -> &'static str {
+    match param {
+        DifficultyParam::InitialHearts => "param-hearts",
+        DifficultyParam::DifficultyStep => "param-step",
+        DifficultyParam::DifficultyUpperLimit => "param-limit",
+        DifficultyParam::BugSpawnRate => "param-spawn-rate",
+        DifficultyParam::BugSpeed => "param-bug-speed",
+    }
+}
+
+fn value_text(param: DifficultyParam, config: &DifficultyConfig) -> String {
+    match param {
+        DifficultyParam::InitialHearts => config.initial_hearts.to_string(),
+        _ => format!("{:.2}", config.get(param)),
+    }
+}
+
+// how far along its range the parameter is, from 0 to 1
+fn fill_fraction(param: DifficultyParam, config: &DifficultyConfig) -> f32 {
+    let (min, max, _) = param.range();
+    (config.get(param) - min) / (max - min)
+}
+
+// label, step down, track, step up and the current value in one row
+pub fn attach_difficulty_slider(
+    parent: &mut ChildBuilder,
+    param: DifficultyParam,
+    config: &DifficultyConfig,
+    locale: &Locale,
+    theme: &Theme,
+) {
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(10.0),
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: row_style,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        locale.get(param_message_id(param)),
+                        text_style.clone(),
+                    ),
+                    style: Style {
+                        width: Val::Px(120.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                LocalizedText(param_message_id(param)),
+            ));
+
+            attach_small_button(parent, ButtonType::StepDifficulty(param, false), "-");
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(150.0),
+                            height: Val::Px(16.0),
+                            ..default()
+                        },
+                        background_color: theme.panel.into(),
+                        ..default()
+                    },
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    DifficultySlider(param),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(fill_fraction(param, config) * 100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: theme.button_hover.into(),
+                            ..default()
+                        },
+                        SliderFill(param),
+                    ));
+                });
+
+            attach_small_button(parent, ButtonType::StepDifficulty(param, true), "+");
+
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(value_text(param, config), text_style),
+                    ..default()
+                },
+                SliderValue(param),
+            ));
+        });
+}
+
+fn drag_difficulty_sliders(
+    query: Query<(&Interaction, &RelativeCursorPosition, &DifficultySlider)>,
+    mut custom: ResMut<CustomDifficulty>,
+) {
+    for (interaction, cursor, slider) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+
+        let (min, max, _) = slider.0.range();
+        let mut updated = custom.0;
+        updated.set(slider.0, min + position.x.clamp(0.0, 1.0) * (max - min));
+
+        // only touch the config when the snapped value moved, so holding still does not save
+        if updated.get(slider.0) != custom.0.get(slider.0) {
+            custom.0 = updated;
+        }
+    }
+}
+
+fn update_difficulty_sliders(
+    mut fill_query: Query<(&mut Style, &SliderFill)>,
+    mut value_query: Query<(&mut Text, &SliderValue)>,
+    custom: Res<CustomDifficulty>,
+) {
+    for (mut style, fill) in &mut fill_query {
+        style.width = Val::Percent(fill_fraction(fill.0, &custom.0) * 100.0);
+    }
+    for (mut text, value) in &mut value_query {
+        text.sections[0].value = value_text(value.0, &custom.0);
+    }
+}
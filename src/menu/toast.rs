@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+const TOAST_DURATION: f32 = 2.5;
+const TOAST_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.9);
+
+#[derive(Event)]
+pub struct ToastEvent(pub String);
+
+#[derive(Component)]
+struct ToastStack;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+pub struct ToastPlugin;
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToastEvent>()
+            .add_systems(Startup, spawn_toast_stack)
+            .add_systems(
+                Update,
+                (spawn_toast.run_if(on_event::<ToastEvent>()), despawn_toast),
+            );
+    }
+}
+
+// toasts outlive state changes, so the stack they live in is never despawned
+fn spawn_toast_stack(mut commands: Commands) {
+    let stack_style = Style {
+        position_type: PositionType::Absolute,
+        flex_direction: FlexDirection::ColumnReverse,
+        align_items: AlignItems::Center,
+        width: Val::Percent(100.0),
+        bottom: Val::Px(10.0),
+        row_gap: Val::Px(5.0),
+        ..default()
+    };
+
+    commands.spawn((
+        NodeBundle {
+            style: stack_style,
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        ToastStack,
+    ));
+}
+
+fn spawn_toast(
+    mut commands: Commands,
+    mut toast_reader: EventReader<ToastEvent>,
+    stack_query: Query<Entity, With<ToastStack>>,
+) {
+    let Ok(stack) = stack_query.get_single() else {
+        return;
+    };
+
+    let toast_style = Style {
+        padding: UiRect::all(Val::Px(8.0)),
+        ..default()
+    };
+
+    let text_style = TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    for ToastEvent(message) in toast_reader.read() {
+        commands.entity(stack).with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: toast_style.clone(),
+                        background_color: TOAST_COLOR.into(),
+                        ..default()
+                    },
+                    Toast(Timer::from_seconds(TOAST_DURATION, TimerMode::Once)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle {
+                        text: Text::from_section(message, text_style.clone()),
+                        ..default()
+                    });
+                });
+        });
+    }
+}
+
+fn despawn_toast(
+    mut commands: Commands,
+    mut toast_query: Query<(Entity, &mut Toast)>,
+    time: Res<Time>,
+) {
+    for (entity, mut toast) in &mut toast_query {
+        if toast.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::Accessibility,
+    cli::LaunchOptions,
+    display::DisplaySettings,
+    game::{
+        difficulty::{CustomDifficulty, DifficultyConfig, DifficultyPreset},
+        touch::TouchControlls,
+        weather::WeatherSettings,
+    },
+    locale::{Language, Locale},
+};
+
+const DEFAULT_SETTINGS_PATH: &str = "settings.ron";
+
+// everything the player can change in the settings menu that outlives a launch
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct SavedSettings {
+    language: Language,
+    hide_touch_controlls: bool,
+    accessibility: Accessibility,
+    difficulty_preset: DifficultyPreset,
+    difficulty: DifficultyConfig, // the custom preset, whichever preset is picked
+    display: DisplaySettings,
+    weather: WeatherSettings,
+}
+
+pub struct PersistencePlugin;
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_settings)
+            .add_systems(
+                Update,
+                save_settings.run_if(
+                    resource_changed::<Locale>()
+                        .or_else(resource_changed::<TouchControlls>())
+                        .or_else(resource_changed::<Accessibility>())
+                        .or_else(resource_changed::<DifficultyPreset>())
+                        .or_else(resource_changed::<CustomDifficulty>())
+                        .or_else(resource_changed::<DisplaySettings>())
+                        .or_else(resource_changed::<WeatherSettings>()),
+                ),
+            )
+            // the window size and position are only written on the way out
+            .add_systems(Last, save_settings.run_if(on_event::<AppExit>()));
+    }
+}
+
+// the web build has no file system, there settings last until the tab is closed
+pub fn read_file(path: &str) -> Option<String> {
+    if cfg!(target_arch = "wasm32") {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+// returns whether the file was written
+pub fn write_file(path: &str, contents: &str) -> bool {
+    if cfg!(target_arch = "wasm32") {
+        return false;
+    }
+    if let Err(error) = std::fs::write(path, contents) {
+        warn!("could not write {}: {}", path, error);
+        return false;
+    }
+    true
+}
+
+// --config swaps the settings file
+fn settings_path(options: &LaunchOptions) -> &str {
+    options.config.as_deref().unwrap_or(DEFAULT_SETTINGS_PATH)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_settings(
+    mut locale: ResMut<Locale>,
+    mut touch_controlls: ResMut<TouchControlls>,
+    mut accessibility: ResMut<Accessibility>,
+    mut difficulty_preset: ResMut<DifficultyPreset>,
+    mut custom_difficulty: ResMut<CustomDifficulty>,
+    mut display: ResMut<DisplaySettings>,
+    mut weather: ResMut<WeatherSettings>,
+    options: Res<LaunchOptions>,
+) {
+    let path = settings_path(&options);
+    let Some(contents) = read_file(path) else {
+        return;
+    };
+
+    let settings: SavedSettings = match ron::from_str(&contents) {
+        Ok(settings) => settings,
+        Err(error) => {
+            warn!("ignoring broken {}: {}", path, error);
+            return;
+        }
+    };
+
+    locale.set_language(settings.language);
+    touch_controlls.hidden = settings.hide_touch_controlls;
+    *accessibility = settings.accessibility;
+    *difficulty_preset = settings.difficulty_preset;
+    custom_difficulty.0 = settings.difficulty;
+    *display = settings.display;
+    *weather = settings.weather;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_settings(
+    locale: Res<Locale>,
+    touch_controlls: Res<TouchControlls>,
+    accessibility: Res<Accessibility>,
+    difficulty_preset: Res<DifficultyPreset>,
+    custom_difficulty: Res<CustomDifficulty>,
+    display: Res<DisplaySettings>,
+    weather: Res<WeatherSettings>,
+    options: Res<LaunchOptions>,
+) {
+    // a replay swaps in the recorded difficulty for its run
+    if options.replay.is_some() {
+        return;
+    }
+
+    let settings = SavedSettings {
+        language: locale.language(),
+        hide_touch_controlls: touch_controlls.hidden,
+        accessibility: *accessibility,
+        difficulty_preset: *difficulty_preset,
+        difficulty: custom_difficulty.0,
+        display: *display,
+        weather: weather.clone(),
+    };
+
+    match ron::ser::to_string_pretty(&settings, default()) {
+        // the change detection fires on the first frame too, an unchanged file is left alone
+        Ok(contents) if read_file(settings_path(&options)).as_deref() == Some(&contents) => (),
+        Ok(contents) => {
+            write_file(settings_path(&options), &contents);
+        }
+        Err(error) => warn!("could not save settings: {}", error),
+    }
+}
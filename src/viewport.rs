@@ -0,0 +1,114 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+
+// the arena is always this many pixels, whatever the window size
+pub const ARENA_WIDTH: f32 = 320.0;
+pub const ARENA_HEIGHT: f32 = 180.0;
+
+const LETTERBOX_COLOR: Color = Color::BLACK;
+// the window camera only sees the upscaled arena, the game camera everything else
+const UPSCALE_LAYER: u8 = 1;
+
+// the camera looking at the arena, moved around by the camera effects
+#[derive(Component)]
+pub struct GameCamera;
+
+// the upscaled picture of the arena shown in the window, tinted by the time of day
+#[derive(Component)]
+pub struct ArenaScreen;
+
+pub struct ViewportPlugin;
+impl Plugin for ViewportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_cameras)
+            .add_systems(PostUpdate, fit_arena_to_window);
+    }
+}
+
+fn spawn_cameras(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: ARENA_WIDTH as u32,
+        height: ARENA_HEIGHT as u32,
+        ..default()
+    };
+    let mut arena_image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("arena"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    arena_image.resize(size);
+    let arena_image = images.add(arena_image);
+
+    // draws the game at its real size into the arena image
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(arena_image.clone()),
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        GameCamera,
+    ));
+
+    // draws the arena image scaled up, with the menus on top at the window's resolution
+    commands.spawn((
+        Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(LETTERBOX_COLOR),
+            },
+            ..default()
+        },
+        RenderLayers::layer(UPSCALE_LAYER),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: arena_image,
+            ..default()
+        },
+        RenderLayers::layer(UPSCALE_LAYER),
+        ArenaScreen,
+    ));
+}
+
+// the biggest whole multiple of the arena that fits, the rest is letterboxed
+fn fit_arena_to_window(
+    mut screen_query: Query<&mut Transform, With<ArenaScreen>>,
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok(mut transform) = screen_query.get_single_mut() else {
+        return;
+    };
+
+    let scale = (window.physical_width() as f32 / ARENA_WIDTH)
+        .min(window.physical_height() as f32 / ARENA_HEIGHT)
+        .floor()
+        .max(1.0);
+    // scaled in physical pixels so every arena pixel covers the same number of screen pixels
+    transform.scale = Vec3::splat(scale / window.scale_factor() as f32);
+}